@@ -0,0 +1,48 @@
+//! Internal code paths exposed for benchmarking.
+//!
+//! Only available with the `bench` feature. These functions are not part of
+//! the stable API; they exist so downstream performance work can measure
+//! the alternatives behind `Bag`'s methods against each other.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use {Bag, Count};
+
+/// Creates a bag pre-sized for `capacity` distinct elements.
+pub fn presized<T: Eq + Hash, C: Count>(capacity: usize) -> Bag<T, C> {
+    Bag(HashMap::with_capacity(capacity))
+}
+
+/// Inserts through the entry API, hashing `elem` once. This is the path
+/// taken by `Bag::put`.
+pub fn put_entry<T: Eq + Hash, C: Count>(bag: &mut Bag<T, C>, elem: T) {
+    *bag.0.entry(elem).or_insert(C::ZERO) += C::ONE;
+}
+
+/// Inserts by looking `elem` up first and only inserting on a miss, hashing
+/// twice for new elements but never moving `elem` into the entry API.
+pub fn put_lookup_first<T: Eq + Hash, C: Count>(bag: &mut Bag<T, C>, elem: T) {
+    if let Some(count) = bag.0.get_mut(&elem) {
+        *count += C::ONE;
+        return;
+    }
+    bag.0.insert(elem, C::ONE);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{presized, put_entry, put_lookup_first};
+    use Bag;
+
+    #[test]
+    fn paths_agree() {
+        let mut a: Bag<u32> = presized(8);
+        let mut b: Bag<u32> = presized(8);
+        for i in 0..100 {
+            put_entry(&mut a, i % 7);
+            put_lookup_first(&mut b, i % 7);
+        }
+        assert_eq!(a, b);
+    }
+}
@@ -0,0 +1,151 @@
+//! Scores for finding collocations: word pairs that occur together more
+//! often than chance, from a bag of unigrams and a bag of bigrams.
+//!
+//! The bigram bag may come from `bigram!`, from `Bag::from_skipgrams` with
+//! a window of two and no skips, or from pairs counted any other way.
+//!
+//! # Examples
+//!
+//! ```
+//! #[macro_use(bagof, bigram)]
+//! extern crate bag;
+//! use bag::collocations::{log_likelihood_ratio, pmi};
+//! fn main() {
+//!     let unigrams = bagof!("ice", "cream", "and", "cake", "and", "ice", "cream");
+//!     let bigrams = bigram!("ice", "cream", "and", "cake", "and", "ice", "cream");
+//!     // Only "ice cream" occurs twice.
+//!     assert_eq!(("ice", "cream"), pmi(&unigrams, &bigrams, 2)[0].0);
+//!     assert_eq!(("ice", "cream"), log_likelihood_ratio(&unigrams, &bigrams, 2)[0].0);
+//! }
+//! ```
+
+use std::cmp::Ordering;
+use std::hash::Hash;
+
+use Bag;
+
+/// Scores every bigram occurring at least `min_count` times by pointwise
+/// mutual information, `log2(P(a, b) / (P(a) P(b)))`, highest first.
+///
+/// Unigram probabilities are relative to `unigrams.len()` and bigram
+/// probabilities to `bigrams.len()`. Bigrams with a word missing from
+/// `unigrams` are skipped. PMI overrates rare pairs, so a `min_count` of
+/// at least a few is advisable.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use(bagof, bigram)]
+/// extern crate bag;
+/// use bag::collocations::pmi;
+/// fn main() {
+///     let unigrams = bagof!(
+///         "new", "york", "is", "big", "and", "new", "york",
+///         "is", "old", "and", "life", "is", "big"
+///     );
+///     let bigrams = bigram!(
+///         "new", "york", "is", "big", "and", "new", "york",
+///         "is", "old", "and", "life", "is", "big"
+///     );
+///     let scores = pmi(&unigrams, &bigrams, 2);
+///     assert_eq!(("new", "york"), scores[0].0);
+/// }
+/// ```
+pub fn pmi<W: Eq + Hash + Clone>(
+    unigrams: &Bag<W>,
+    bigrams: &Bag<(W, W)>,
+    min_count: usize,
+) -> Vec<((W, W), f64)> {
+    let n_unigrams = unigrams.len() as f64;
+    let n_bigrams = bigrams.len() as f64;
+    score(unigrams, bigrams, min_count, |ab, a, b| {
+        let p_ab = ab / n_bigrams;
+        let p_a = a / n_unigrams;
+        let p_b = b / n_unigrams;
+        (p_ab / (p_a * p_b)).log2()
+    })
+}
+
+/// Scores every bigram occurring at least `min_count` times by Dunning's
+/// log-likelihood ratio, highest first.
+///
+/// Each pair `(a, b)` is tested on the 2×2 table of bigrams with and
+/// without `a` first and `b` second, estimating the marginals from the
+/// unigram counts. Unlike PMI the score stays reliable for rare pairs, and
+/// it is distributed as chi-squared with one degree of freedom, so scores
+/// above 10.83 are significant at the 0.001 level.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use(bagof, bigram)]
+/// extern crate bag;
+/// use bag::collocations::log_likelihood_ratio;
+/// fn main() {
+///     let unigrams = bagof!(
+///         "new", "york", "is", "big", "and", "new", "york",
+///         "is", "old", "and", "life", "is", "big"
+///     );
+///     let bigrams = bigram!(
+///         "new", "york", "is", "big", "and", "new", "york",
+///         "is", "old", "and", "life", "is", "big"
+///     );
+///     let scores = log_likelihood_ratio(&unigrams, &bigrams, 1);
+///     assert_eq!(("new", "york"), scores[0].0);
+/// }
+/// ```
+pub fn log_likelihood_ratio<W: Eq + Hash + Clone>(
+    unigrams: &Bag<W>,
+    bigrams: &Bag<(W, W)>,
+    min_count: usize,
+) -> Vec<((W, W), f64)> {
+    let n = bigrams.len() as f64;
+    score(unigrams, bigrams, min_count, |ab, a, b| {
+        let k11 = ab;
+        let k12 = (a - ab).max(0.0);
+        let k21 = (b - ab).max(0.0);
+        let k22 = (n - k11 - k12 - k21).max(0.0);
+        let total = k11 + k12 + k21 + k22;
+        let cells = x_ln_x(k11) + x_ln_x(k12) + x_ln_x(k21) + x_ln_x(k22);
+        let rows = x_ln_x(k11 + k12) + x_ln_x(k21 + k22);
+        let columns = x_ln_x(k11 + k21) + x_ln_x(k12 + k22);
+        (2.0 * (cells - rows - columns + x_ln_x(total))).max(0.0)
+    })
+}
+
+fn x_ln_x(x: f64) -> f64 {
+    if x > 0.0 {
+        x * x.ln()
+    } else {
+        0.0
+    }
+}
+
+/// Scores the bigrams occurring at least `min_count` times by applying
+/// `f` to the counts of the pair and of each word, highest score first.
+fn score<W, F>(
+    unigrams: &Bag<W>,
+    bigrams: &Bag<(W, W)>,
+    min_count: usize,
+    f: F,
+) -> Vec<((W, W), f64)>
+where
+    W: Eq + Hash + Clone,
+    F: Fn(f64, f64, f64) -> f64,
+{
+    let mut scores: Vec<((W, W), f64)> = bigrams
+        .0
+        .iter()
+        .filter(|&(_, &count)| count >= min_count)
+        .filter_map(|(pair, &count)| {
+            let a = unigrams.occurrence(&pair.0);
+            let b = unigrams.occurrence(&pair.1);
+            if a == 0 || b == 0 {
+                return None;
+            }
+            Some((pair.clone(), f(count as f64, a as f64, b as f64)))
+        })
+        .collect();
+    scores.sort_by(|x, y| y.1.partial_cmp(&x.1).unwrap_or(Ordering::Equal));
+    scores
+}
@@ -0,0 +1,165 @@
+use std::hash::Hash;
+
+use {Bag, Count};
+
+/// A multi-field key, such as `(user, endpoint, status)`, whose fields can
+/// be selected at runtime to regroup a `Bag`.
+///
+/// Every field has the same type `V`. For keys mixing field types, count
+/// tuples instead and regroup them with `Bag::group_by_tuple_field`.
+///
+/// # Examples
+///
+/// ```
+/// use bag::{Bag, CompositeKey};
+/// let mut bag = Bag::new();
+/// bag.put(CompositeKey::new().field("alice").field("/login").field("200"));
+/// bag.put(CompositeKey::new().field("alice").field("/login").field("500"));
+/// bag.put(CompositeKey::new().field("bob").field("/login").field("200"));
+///
+/// let by_status = bag.group_by_field(2);
+/// assert_eq!(2, by_status.occurrence(&"200"));
+///
+/// let by_user_status = bag.group_by_fields(&[0, 2]);
+/// assert_eq!(1, by_user_status.occurrence(&CompositeKey::from(vec!["alice", "500"])));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CompositeKey<V>(Vec<V>);
+
+impl<V> CompositeKey<V> {
+    /// Creates a key with no fields.
+    pub fn new() -> Self {
+        CompositeKey(Vec::new())
+    }
+
+    /// Appends a field.
+    pub fn field(mut self, value: V) -> Self {
+        self.0.push(value);
+        self
+    }
+
+    /// Returns the field at `index`.
+    pub fn get(&self, index: usize) -> Option<&V> {
+        self.0.get(index)
+    }
+
+    pub fn fields(&self) -> &[V] {
+        &self.0
+    }
+
+    /// Returns the number of fields.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<V: Clone> CompositeKey<V> {
+    /// Builds a key from the fields at `indices`, in that order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an index is out of range.
+    pub fn project(&self, indices: &[usize]) -> Self {
+        CompositeKey(indices.iter().map(|&i| self.0[i].clone()).collect())
+    }
+}
+
+impl<V> From<Vec<V>> for CompositeKey<V> {
+    fn from(fields: Vec<V>) -> Self {
+        CompositeKey(fields)
+    }
+}
+
+impl<V: Eq + Hash + Clone, C: Count> Bag<CompositeKey<V>, C> {
+    /// Collapses the bag onto the single field at `index`, summing the
+    /// counts of keys that share it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a key has no field at `index`.
+    pub fn group_by_field(&self, index: usize) -> Bag<V, C> {
+        let mut grouped = Bag::default();
+        for (key, &count) in &self.0 {
+            *grouped.0.entry(key.0[index].clone()).or_insert(C::ZERO) += count;
+        }
+        grouped
+    }
+
+    /// Collapses the bag onto the fields at `indices`, summing the counts of
+    /// keys that agree on them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a key lacks one of the fields.
+    pub fn group_by_fields(&self, indices: &[usize]) -> Self {
+        let mut grouped = Bag::default();
+        for (key, &count) in &self.0 {
+            *grouped.0.entry(key.project(indices)).or_insert(C::ZERO) += count;
+        }
+        grouped
+    }
+}
+
+/// Access to the field at position `I` of a tuple key, for
+/// `Bag::group_by_tuple_field`. Implemented for tuples of two to four
+/// fields.
+pub trait TupleField<const I: usize> {
+    type Value;
+
+    /// Returns the field.
+    fn field(&self) -> &Self::Value;
+}
+
+macro_rules! impl_field {
+    ( @one [ $( $t:ident ),+ ] $i:tt $out:ident ) => {
+        impl<$( $t ),+> TupleField<$i> for ( $( $t, )+ ) {
+            type Value = $out;
+
+            #[inline]
+            fn field(&self) -> &$out {
+                &self.$i
+            }
+        }
+    };
+    ( $gens:tt $( $i:tt => $out:ident ),+ ) => {
+        $( impl_field!(@one $gens $i $out); )+
+    };
+}
+
+impl_field!([A, B] 0 => A, 1 => B);
+impl_field!([A, B, C] 0 => A, 1 => B, 2 => C);
+impl_field!([A, B, C, D] 0 => A, 1 => B, 2 => C, 3 => D);
+
+impl<T: Eq + Hash, C: Count> Bag<T, C> {
+    /// Collapses a bag of tuple keys onto the field at position `I`,
+    /// summing the counts of keys that share it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::Bag;
+    /// let mut bag = Bag::new();
+    /// bag.put(("alice", "/login", 200));
+    /// bag.put(("alice", "/login", 500));
+    /// bag.put(("bob", "/login", 200));
+    ///
+    /// let by_status: Bag<u16> = bag.group_by_tuple_field::<2>();
+    /// assert_eq!(2, by_status.occurrence(&200));
+    /// assert_eq!(2, bag.group_by_tuple_field::<0>().occurrence(&"alice"));
+    /// ```
+    pub fn group_by_tuple_field<const I: usize>(&self) -> Bag<T::Value, C>
+    where
+        T: TupleField<I>,
+        T::Value: Eq + Hash + Clone,
+    {
+        let mut grouped = Bag::default();
+        for (key, &count) in &self.0 {
+            *grouped.0.entry(key.field().clone()).or_insert(C::ZERO) += count;
+        }
+        grouped
+    }
+}
@@ -0,0 +1,245 @@
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::sync::{PoisonError, RwLock};
+use std::thread;
+
+use {Bag, Count};
+
+const DEFAULT_SHARDS: usize = 16;
+/// Elements handed to a `count_parallel` worker per message.
+const BATCH: usize = 1024;
+
+type Shard<T> = RwLock<HashMap<T, AtomicUsize>>;
+
+/// A bag that many threads can insert into through a shared reference.
+///
+/// Elements are spread over independently locked shards. Counting an
+/// element already present only takes a shard's read lock and bumps an
+/// atomic counter; only the first occurrence of an element takes a write
+/// lock.
+#[derive(Debug)]
+pub struct ConcurrentBag<T: Eq + Hash> {
+    shards: Vec<Shard<T>>,
+    hasher: RandomState,
+}
+
+impl<T: Eq + Hash> Default for ConcurrentBag<T> {
+    fn default() -> Self {
+        ConcurrentBag::new()
+    }
+}
+
+impl<T: Eq + Hash> ConcurrentBag<T> {
+    /// Creates an empty bag with a default number of shards.
+    pub fn new() -> Self {
+        ConcurrentBag::with_shards(DEFAULT_SHARDS)
+    }
+
+    /// Creates an empty bag split over `shards` independently locked maps.
+    /// More shards reduce contention between threads inserting new elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shards` is zero.
+    pub fn with_shards(shards: usize) -> Self {
+        assert!(shards > 0, "a concurrent bag needs at least one shard");
+        ConcurrentBag {
+            shards: (0..shards).map(|_| RwLock::new(HashMap::new())).collect(),
+            hasher: RandomState::new(),
+        }
+    }
+
+    /// Insert an element.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use bag::ConcurrentBag;
+    /// let bag = ConcurrentBag::new();
+    /// thread::scope(|s| {
+    ///     for _ in 0..4 {
+    ///         s.spawn(|| {
+    ///             for word in "a b a".split(' ') {
+    ///                 bag.put(word);
+    ///             }
+    ///         });
+    ///     }
+    /// });
+    /// assert_eq!(8, bag.occurrence(&"a"));
+    /// assert_eq!(4, bag.occurrence(&"b"));
+    /// ```
+    pub fn put(&self, elem: T) {
+        let shard = self.shard(&elem);
+        {
+            let map = shard.read().unwrap_or_else(PoisonError::into_inner);
+            if let Some(count) = map.get(&elem) {
+                count.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+        }
+        let mut map = shard.write().unwrap_or_else(PoisonError::into_inner);
+        map.entry(elem)
+            .or_insert_with(|| AtomicUsize::new(0))
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counts the occurrences of `elem`.
+    pub fn occurrence(&self, elem: &T) -> usize {
+        let map = self
+            .shard(elem)
+            .read()
+            .unwrap_or_else(PoisonError::into_inner);
+        map.get(elem)
+            .map_or(0, |count| count.load(Ordering::Relaxed))
+    }
+
+    /// Consumes the concurrent bag, returning the counts as an ordinary bag.
+    pub fn into_bag(self) -> Bag<T> {
+        let mut bag = Bag::new();
+        for shard in self.shards {
+            let map = shard.into_inner().unwrap_or_else(PoisonError::into_inner);
+            bag.0
+                .extend(map.into_iter().map(|(e, c)| (e, c.into_inner())));
+        }
+        bag
+    }
+
+    fn shard(&self, elem: &T) -> &Shard<T> {
+        let i = self.hasher.hash_one(elem) as usize % self.shards.len();
+        &self.shards[i]
+    }
+}
+
+impl<T: Eq + Hash + Clone> ConcurrentBag<T> {
+    /// Copies the current counts into an ordinary bag.
+    ///
+    /// Each shard is read atomically, but insertions racing with the
+    /// snapshot may or may not be included.
+    pub fn snapshot(&self) -> Bag<T> {
+        let mut bag = Bag::new();
+        for shard in &self.shards {
+            let map = shard.read().unwrap_or_else(PoisonError::into_inner);
+            bag.0.reserve(map.len());
+            for (elem, count) in map.iter() {
+                bag.0.insert(elem.clone(), count.load(Ordering::Relaxed));
+            }
+        }
+        bag
+    }
+}
+
+impl<T: Eq + Hash + Send, C: Count + Send> Bag<T, C> {
+    /// Counts `items` on `num_shards` worker threads.
+    ///
+    /// The calling thread routes every element by hash to one worker, so
+    /// each distinct element is counted by exactly one private bag and the
+    /// shards are merged without combining any counts.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_shards` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::Bag;
+    /// let text = "the cat and the hat";
+    /// let bag: Bag<&str> = Bag::count_parallel(text.split(' '), 4);
+    /// assert_eq!(2, bag.occurrence(&"the"));
+    /// assert_eq!(4, bag.distinct_len());
+    /// ```
+    pub fn count_parallel<I>(items: I, num_shards: usize) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        assert!(num_shards > 0, "parallel counting needs at least one shard");
+        let hasher = RandomState::new();
+        let shards = thread::scope(|s| {
+            let mut senders = Vec::with_capacity(num_shards);
+            let mut workers = Vec::with_capacity(num_shards);
+            for _ in 0..num_shards {
+                let (tx, rx) = mpsc::sync_channel::<Vec<T>>(2);
+                senders.push(tx);
+                workers.push(s.spawn(move || {
+                    let mut bag = Bag::default();
+                    for batch in rx {
+                        bag.extend(batch);
+                    }
+                    bag
+                }));
+            }
+
+            let mut batches: Vec<Vec<T>> = (0..num_shards).map(|_| Vec::new()).collect();
+            for elem in items {
+                let i = hasher.hash_one(&elem) as usize % num_shards;
+                batches[i].push(elem);
+                if batches[i].len() == BATCH {
+                    let full = std::mem::replace(&mut batches[i], Vec::with_capacity(BATCH));
+                    senders[i].send(full).expect("worker alive");
+                }
+            }
+            for (tx, batch) in senders.into_iter().zip(batches) {
+                if !batch.is_empty() {
+                    tx.send(batch).expect("worker alive");
+                }
+            }
+
+            workers
+                .into_iter()
+                .map(|worker| worker.join().expect("counting worker panicked"))
+                .collect::<Vec<Bag<T, C>>>()
+        });
+
+        let distinct = shards.iter().map(Bag::distinct_len).sum();
+        let mut bag = Bag(HashMap::with_capacity(distinct));
+        for shard in shards {
+            bag.0.extend(shard.0);
+        }
+        bag
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::ConcurrentBag;
+
+    #[test]
+    fn threads_agree_with_sequential() {
+        let bag = ConcurrentBag::with_shards(3);
+        thread::scope(|s| {
+            for t in 0..8u32 {
+                let bag = &bag;
+                s.spawn(move || {
+                    for i in 0..1000 {
+                        bag.put((i * (t + 1)) % 37);
+                    }
+                });
+            }
+        });
+
+        let mut expected = bagof!();
+        for t in 0..8u32 {
+            for i in 0..1000 {
+                expected.put((i * (t + 1)) % 37);
+            }
+        }
+        assert_eq!(expected, bag.snapshot());
+        assert_eq!(expected, bag.into_bag());
+    }
+
+    #[test]
+    fn count_parallel_matches_sequential() {
+        use Bag;
+
+        let items = (0..10_000u32).map(|i| i * i % 101);
+        let expected: Bag<u32> = items.clone().collect();
+        assert_eq!(expected, Bag::count_parallel(items.clone(), 1));
+        assert_eq!(expected, Bag::count_parallel(items, 7));
+    }
+}
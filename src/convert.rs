@@ -0,0 +1,217 @@
+use std::collections::hash_map::Entry::{Occupied, Vacant};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::iter::{FromIterator, Sum};
+
+use error::FrequencyError;
+use {Bag, Count};
+
+/// Wraps an existing count map, dropping entries with a zero count.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+/// use bag::Bag;
+/// let mut counts = HashMap::new();
+/// counts.insert("a", 2);
+/// counts.insert("b", 0);
+/// let bag: Bag<_> = Bag::from(counts);
+/// assert_eq!(2, bag.occurrence(&"a"));
+/// assert_eq!(1, bag.distinct_len());
+/// ```
+impl<T: Eq + Hash, C: Count> From<HashMap<T, C>> for Bag<T, C> {
+    fn from(mut map: HashMap<T, C>) -> Self {
+        map.retain(|_, count| *count != C::ZERO);
+        Bag(map)
+    }
+}
+
+impl<T: Eq + Hash, C: Count> Extend<T> for Bag<T, C> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.put(elem);
+        }
+    }
+}
+
+impl<T: Eq + Hash, C: Count> FromIterator<T> for Bag<T, C> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut bag = Bag::default();
+        bag.extend(iter);
+        bag
+    }
+}
+
+/// Adds up the counts of many bags, as `merge` does for two.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use(bagof)]
+/// extern crate bag;
+/// use bag::Bag;
+/// fn main() {
+///     let chunks = vec![bagof!("a", "b"), bagof!("a"), bagof!()];
+///     let total: Bag<_> = chunks.into_iter().sum();
+///     assert_eq!(bagof!("a", "a", "b"), total);
+/// }
+/// ```
+impl<T: Eq + Hash, C: Count> Sum for Bag<T, C> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Bag::default(), |mut total, bag| {
+            total.merge(bag);
+            total
+        })
+    }
+}
+
+/// Counts every element of the vector, reserving room for all of them up
+/// front.
+///
+/// # Examples
+///
+/// ```
+/// use bag::Bag;
+/// let bag: Bag<_> = Bag::from(vec!["a", "b", "a"]);
+/// assert_eq!(2, bag.occurrence(&"a"));
+/// ```
+impl<T: Eq + Hash, C: Count> From<Vec<T>> for Bag<T, C> {
+    fn from(elems: Vec<T>) -> Self {
+        let mut bag = Bag(HashMap::with_capacity(elems.len()));
+        bag.extend(elems);
+        bag
+    }
+}
+
+impl<'a, T: Eq + Hash + Clone, C: Count> From<&'a [T]> for Bag<T, C> {
+    fn from(elems: &'a [T]) -> Self {
+        let mut bag = Bag(HashMap::with_capacity(elems.len()));
+        bag.extend(elems.iter().cloned());
+        bag
+    }
+}
+
+impl<T: Eq + Hash, C: Count> Bag<T, C> {
+    /// Builds a bag from `(element, count)` pairs.
+    ///
+    /// A bag never stores an element with a zero count, so such pairs are
+    /// rejected. An element listed more than once gets the sum of its
+    /// counts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::{Bag, FrequencyError};
+    /// let bag: Bag<_> = Bag::from_frequencies(vec![("a", 2), ("b", 1), ("a", 1)]).unwrap();
+    /// assert_eq!(3, bag.occurrence(&"a"));
+    ///
+    /// let zero: Result<Bag<_>, _> = Bag::from_frequencies(vec![("a", 0)]);
+    /// assert_eq!(Err(FrequencyError::ZeroCount), zero);
+    /// ```
+    pub fn from_frequencies<I>(frequencies: I) -> Result<Self, FrequencyError>
+    where
+        I: IntoIterator<Item = (T, C)>,
+    {
+        let frequencies = frequencies.into_iter();
+        let mut map = HashMap::with_capacity(frequencies.size_hint().0);
+        for (elem, count) in frequencies {
+            if count == C::ZERO {
+                return Err(FrequencyError::ZeroCount);
+            }
+            match map.entry(elem) {
+                Vacant(view) => {
+                    view.insert(count);
+                }
+                Occupied(mut view) => {
+                    let sum = view
+                        .get()
+                        .checked_add(count)
+                        .ok_or(FrequencyError::Overflow)?;
+                    *view.get_mut() = sum;
+                }
+            }
+        }
+        Ok(Bag(map))
+    }
+
+    /// Counts every element of `elems`, cloning one copy of each distinct
+    /// element.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::Bag;
+    /// let words = ["a", "b", "a"];
+    /// let bag: Bag<_> = Bag::from_slice(&words);
+    /// assert_eq!(2, bag.occurrence(&"a"));
+    /// assert_eq!(3, bag.len());
+    /// ```
+    pub fn from_slice(elems: &[T]) -> Self
+    where
+        T: Clone,
+    {
+        Bag::from(elems)
+    }
+
+    /// Consumes the bag, returning the map from each distinct element to its
+    /// count. Absent elements have no entry.
+    pub fn into_inner(self) -> HashMap<T, C> {
+        self.0
+    }
+
+    /// Returns the map from each distinct element to its count.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let bag = bagof!("a", "b", "a");
+    ///     assert_eq!(Some(&2), bag.as_map().get(&"a"));
+    ///     assert_eq!(None, bag.as_map().get(&"z"));
+    /// }
+    /// ```
+    pub fn as_map(&self) -> &HashMap<T, C> {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use Bag;
+
+    #[test]
+    fn map_round_trip() {
+        let bag = bagof!('a', 'b', 'a');
+        let map = bag.clone().into_inner();
+        assert_eq!(2, map.len());
+        assert_eq!(bag, Bag::from(map));
+
+        let empty: HashMap<char, u8> = vec![('x', 0)].into_iter().collect();
+        assert!(Bag::from(empty).is_empty());
+    }
+
+    #[test]
+    fn frequency_overflow() {
+        use error::FrequencyError;
+
+        let table = vec![('a', 200u8), ('a', 100)];
+        assert_eq!(Err(FrequencyError::Overflow), Bag::from_frequencies(table));
+    }
+
+    #[test]
+    fn counts_sequences() {
+        let chars: Vec<char> = "abracadabra".chars().collect();
+        let expected: Bag<char> = chars.iter().cloned().collect();
+        assert_eq!(expected, Bag::from_slice(&chars));
+        assert_eq!(expected, Bag::from(chars));
+
+        let mut bag: Bag<char> = Bag::from(vec!['a']);
+        bag.extend("aa".chars());
+        assert_eq!(3, bag.occurrence(&'a'));
+    }
+}
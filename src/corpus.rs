@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use Bag;
+
+/// A collection of named `Bag` documents with incrementally maintained
+/// document frequencies, for TF-IDF style weighting.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use(bagof)]
+/// extern crate bag;
+/// use bag::Corpus;
+/// fn main() {
+///     let mut corpus = Corpus::new();
+///     corpus.insert("a", bagof!("the", "cat", "sat"));
+///     corpus.insert("b", bagof!("the", "dog", "sat"));
+///     corpus.insert("c", bagof!("the", "end"));
+///
+///     assert_eq!(0.0, corpus.idf(&"the"));
+///     assert!(corpus.tf_idf("a", &"cat").unwrap() > corpus.tf_idf("a", &"sat").unwrap());
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Corpus<E: Eq + Hash> {
+    docs: HashMap<String, Bag<E>>,
+    df: Bag<E>,
+}
+
+impl<E: Eq + Hash> Default for Corpus<E> {
+    fn default() -> Self {
+        Corpus {
+            docs: HashMap::new(),
+            df: Bag::new(),
+        }
+    }
+}
+
+impl<E: Eq + Hash + Clone> Corpus<E> {
+    /// Creates an empty corpus.
+    pub fn new() -> Self {
+        Corpus::default()
+    }
+
+    /// Adds the document `doc` under `name`, returning the document it
+    /// replaces, if any.
+    pub fn insert<N: Into<String>>(&mut self, name: N, doc: Bag<E>) -> Option<Bag<E>> {
+        for term in doc.0.keys() {
+            self.df.put(term.clone());
+        }
+        let old = self.docs.insert(name.into(), doc);
+        if let Some(ref old) = old {
+            self.forget(old);
+        }
+        old
+    }
+
+    /// Removes the document `name`, returning it.
+    pub fn remove(&mut self, name: &str) -> Option<Bag<E>> {
+        let doc = self.docs.remove(name)?;
+        self.forget(&doc);
+        Some(doc)
+    }
+
+    /// Decrements the document frequency of every term of `doc`.
+    fn forget(&mut self, doc: &Bag<E>) {
+        for term in doc.0.keys() {
+            let gone = match self.df.0.get_mut(term) {
+                Some(df) => {
+                    *df -= 1;
+                    *df == 0
+                }
+                None => false,
+            };
+            if gone {
+                self.df.0.remove(term);
+            }
+        }
+    }
+}
+
+impl<E: Eq + Hash> Corpus<E> {
+    /// Returns the number of documents.
+    pub fn len(&self) -> usize {
+        self.docs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.docs.is_empty()
+    }
+
+    /// Returns the document `name`.
+    pub fn get(&self, name: &str) -> Option<&Bag<E>> {
+        self.docs.get(name)
+    }
+
+    /// Iterates over the documents and their names.
+    pub fn documents<'a>(&'a self) -> impl Iterator<Item = (&'a str, &'a Bag<E>)> + 'a {
+        self.docs.iter().map(|(name, doc)| (name.as_str(), doc))
+    }
+
+    /// Returns the number of documents containing `term`.
+    pub fn document_frequency(&self, term: &E) -> usize {
+        self.df.occurrence(term)
+    }
+
+    /// Returns the inverse document frequency `ln(N / df)` of `term`, or
+    /// zero if no document contains it.
+    pub fn idf(&self, term: &E) -> f64 {
+        match self.document_frequency(term) {
+            0 => 0.0,
+            df => (self.docs.len() as f64 / df as f64).ln(),
+        }
+    }
+
+    /// Returns the TF-IDF weight of `term` in the document `name`, with the
+    /// term frequency relative to the document length, or `None` if there
+    /// is no such document.
+    pub fn tf_idf(&self, name: &str, term: &E) -> Option<f64> {
+        let doc = self.docs.get(name)?;
+        Some(term_frequency(doc, term) * self.idf(term))
+    }
+
+    /// Returns the TF-IDF weight of every term of the document `name`, or
+    /// `None` if there is no such document.
+    pub fn weighted_vector(&self, name: &str) -> Option<HashMap<&E, f64>> {
+        let doc = self.docs.get(name)?;
+        Some(
+            doc.0
+                .keys()
+                .map(|term| (term, term_frequency(doc, term) * self.idf(term)))
+                .collect(),
+        )
+    }
+
+    /// Ranks the documents sharing a term with `query` by Okapi BM25,
+    /// highest score first, breaking ties by name.
+    ///
+    /// `k1` controls how quickly repeated terms saturate and `b` how much
+    /// longer-than-average documents are penalized; `1.2` and `0.75` are
+    /// customary. A term repeated in the query counts once per occurrence.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// use bag::Corpus;
+    /// fn main() {
+    ///     let mut corpus = Corpus::new();
+    ///     corpus.insert("a", bagof!("the", "cat", "sat"));
+    ///     corpus.insert("b", bagof!("the", "cat", "cat", "sat", "on", "the", "mat"));
+    ///     corpus.insert("c", bagof!("the", "dog"));
+    ///
+    ///     let ranked = corpus.bm25(&bagof!("cat"), 1.2, 0.75);
+    ///     assert_eq!(vec!["b", "a"], ranked.iter().map(|r| r.0).collect::<Vec<_>>());
+    /// }
+    /// ```
+    pub fn bm25(&self, query: &Bag<E>, k1: f64, b: f64) -> Vec<(&str, f64)> {
+        let n = self.docs.len() as f64;
+        let total: usize = self.docs.values().map(Bag::len).sum();
+        let avg_len = total as f64 / n;
+        let idfs: Vec<(&E, f64, f64)> = query
+            .0
+            .iter()
+            .filter_map(|(term, &weight)| match self.document_frequency(term) {
+                0 => None,
+                df => {
+                    let df = df as f64;
+                    Some((
+                        term,
+                        weight as f64,
+                        ((n - df + 0.5) / (df + 0.5) + 1.0).ln(),
+                    ))
+                }
+            })
+            .collect();
+
+        let mut ranked: Vec<(&str, f64)> = self
+            .docs
+            .iter()
+            .filter_map(|(name, doc)| {
+                let norm = k1 * (1.0 - b + b * doc.len() as f64 / avg_len);
+                let mut matched = false;
+                let mut score = 0.0;
+                for &(term, weight, idf) in &idfs {
+                    let tf = doc.occurrence(term) as f64;
+                    if tf > 0.0 {
+                        matched = true;
+                        score += weight * idf * tf * (k1 + 1.0) / (tf + norm);
+                    }
+                }
+                if matched {
+                    Some((name.as_str(), score))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        ranked.sort_by(|x, y| {
+            y.1.partial_cmp(&x.1)
+                .expect("score is not NaN")
+                .then(x.0.cmp(y.0))
+        });
+        ranked
+    }
+}
+
+fn term_frequency<E: Eq + Hash>(doc: &Bag<E>, term: &E) -> f64 {
+    match doc.len() {
+        0 => 0.0,
+        len => doc.occurrence(term) as f64 / len as f64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Corpus;
+
+    #[test]
+    fn document_frequencies_follow_updates() {
+        let mut corpus = Corpus::new();
+        corpus.insert("a", bagof!('x', 'x', 'y'));
+        corpus.insert("b", bagof!('x'));
+        assert_eq!(2, corpus.document_frequency(&'x'));
+        assert_eq!(1, corpus.document_frequency(&'y'));
+
+        let old = corpus.insert("a", bagof!('z'));
+        assert_eq!(Some(bagof!('x', 'x', 'y')), old);
+        assert_eq!(1, corpus.document_frequency(&'x'));
+        assert_eq!(0, corpus.document_frequency(&'y'));
+
+        corpus.remove("b");
+        assert_eq!(0, corpus.document_frequency(&'x'));
+        assert_eq!(1, corpus.len());
+
+        let weights = corpus.weighted_vector("a").unwrap();
+        assert_eq!(Some(&0.0), weights.get(&'z'));
+        assert_eq!(None, corpus.tf_idf("b", &'x'));
+    }
+}
@@ -0,0 +1,91 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Mul, MulAssign, Sub, SubAssign};
+
+/// An unsigned integer type used to store multiplicities in a `Bag`.
+///
+/// Implemented for all of the primitive unsigned integers, so a `Bag` can
+/// count in `u32` to save memory or in `u128` to push overflow further away.
+pub trait Count:
+    Copy
+    + Ord
+    + Hash
+    + Debug
+    + Default
+    + Add<Output = Self>
+    + AddAssign
+    + Sub<Output = Self>
+    + SubAssign
+    + Mul<Output = Self>
+    + MulAssign
+    + Sum
+{
+    /// The count of an absent element.
+    const ZERO: Self;
+    /// The count added by a single `put`.
+    const ONE: Self;
+
+    /// Adds `rhs`, returning `None` on overflow.
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+
+    /// Adds `rhs`, stopping at the largest value of `Self`.
+    fn saturating_add(self, rhs: Self) -> Self;
+
+    /// Subtracts `rhs`, returning `None` on underflow.
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+
+    /// Multiplies by `rhs`, returning `None` on overflow.
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+
+    /// Converts the count to `f64`, rounding to the nearest representable
+    /// value.
+    fn to_f64(self) -> f64;
+
+    /// Converts from `f64`, truncating toward zero and saturating at the
+    /// bounds of `Self`.
+    fn from_f64(x: f64) -> Self;
+}
+
+macro_rules! impl_count {
+    ( $( $t:ty ),* ) => {
+        $(
+            impl Count for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+
+                #[inline]
+                fn checked_add(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_add(self, rhs)
+                }
+
+                #[inline]
+                fn saturating_add(self, rhs: Self) -> Self {
+                    <$t>::saturating_add(self, rhs)
+                }
+
+                #[inline]
+                fn checked_sub(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_sub(self, rhs)
+                }
+
+                #[inline]
+                fn checked_mul(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_mul(self, rhs)
+                }
+
+                #[inline]
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+
+                #[inline]
+                fn from_f64(x: f64) -> Self {
+                    x as $t
+                }
+            }
+        )*
+    };
+}
+
+impl_count!(u8, u16, u32, u64, u128, usize);
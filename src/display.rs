@@ -0,0 +1,236 @@
+use std::fmt;
+use std::hash::Hash;
+
+use {Bag, Count};
+
+/// Renders the bag in multiset notation, `{a: 3, b: 1}`, in arbitrary
+/// order; see `Bag::display_sorted` for a stable rendering.
+impl<T: Eq + Hash + fmt::Display, C: Count + fmt::Display> fmt::Display for Bag<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_entries(f, self.0.iter())
+    }
+}
+
+/// Renders a `Bag` in multiset notation ordered by element, created by
+/// `Bag::display_sorted`.
+pub struct DisplaySorted<'a, T: Eq + Hash + 'a, C: Count + 'a>(&'a Bag<T, C>);
+
+impl<'a, T, C> fmt::Display for DisplaySorted<'a, T, C>
+where
+    T: Eq + Hash + Ord + fmt::Display,
+    C: Count + fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut entries: Vec<_> = self.0 .0.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        write_entries(f, entries.into_iter())
+    }
+}
+
+impl<T: Eq + Hash + Ord, C: Count> Bag<T, C> {
+    /// Returns an adapter displaying the bag with its elements in order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let bag = bagof!('a', 'b', 'r', 'a', 'c', 'a', 'd', 'a', 'b', 'r', 'a');
+    ///     assert_eq!("{a: 5, b: 2, c: 1, d: 1, r: 2}", bag.display_sorted().to_string());
+    /// }
+    /// ```
+    pub fn display_sorted(&self) -> DisplaySorted<'_, T, C> {
+        DisplaySorted(self)
+    }
+}
+
+/// Renders a `Bag` as a bar chart, most frequent element first, created by
+/// `Bag::histogram`.
+pub struct Histogram<'a, T: Eq + Hash + 'a, C: Count + 'a> {
+    bag: &'a Bag<T, C>,
+    width: usize,
+    top: Option<usize>,
+}
+
+impl<'a, T: Eq + Hash, C: Count> Histogram<'a, T, C> {
+    /// Shows only the `n` most frequent elements.
+    pub fn top(mut self, n: usize) -> Self {
+        self.top = Some(n);
+        self
+    }
+}
+
+impl<'a, T, C> fmt::Display for Histogram<'a, T, C>
+where
+    T: Eq + Hash + Ord + fmt::Display,
+    C: Count + fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut entries: Vec<_> = self.bag.0.iter().collect();
+        entries.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        if let Some(n) = self.top {
+            entries.truncate(n);
+        }
+        let labels: Vec<String> = entries.iter().map(|(elem, _)| elem.to_string()).collect();
+        let label_width = labels.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+        let max = entries.first().map_or(0.0, |(_, count)| count.to_f64());
+        for (label, (_, count)) in labels.iter().zip(&entries) {
+            write!(f, "{:<w$} | ", label, w = label_width)?;
+            if self.width > 0 {
+                // Every element present gets at least one mark.
+                let bar = ((count.to_f64() / max * self.width as f64).round() as usize).max(1);
+                write!(f, "{} ", "#".repeat(bar))?;
+            }
+            writeln!(f, "{}", count)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Eq + Hash + Ord, C: Count> Bag<T, C> {
+    /// Returns an adapter displaying the counts as a bar chart whose longest
+    /// bar is `width` characters. A `width` of zero shows the counts alone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let bag = bagof!('a', 'b', 'r', 'a', 'c', 'a', 'd', 'a', 'b', 'r', 'a');
+    ///     let chart = bag.histogram(10).top(3).to_string();
+    ///     assert_eq!("a | ########## 5\nb | #### 2\nr | #### 2\n", chart);
+    /// }
+    /// ```
+    pub fn histogram(&self, width: usize) -> Histogram<'_, T, C> {
+        Histogram {
+            bag: self,
+            width,
+            top: None,
+        }
+    }
+
+    /// Renders the counts as a bar chart; see `histogram`.
+    pub fn render_histogram(&self, width: usize) -> String
+    where
+        T: fmt::Display,
+        C: fmt::Display,
+    {
+        self.histogram(width).to_string()
+    }
+}
+
+impl<W, C> Bag<(W, W), C>
+where
+    W: Eq + Hash + Ord + fmt::Display,
+    C: Count + fmt::Display,
+{
+    /// Renders the pairs counted at least `min_count` times as a Graphviz
+    /// directed graph, each edge labelled with its count and ordered by its
+    /// endpoints.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bigram)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let bigrams = bigram!('a', 'b', 'a', 'b');
+    ///     assert_eq!(
+    ///         "digraph {\n    \"a\" -> \"b\" [label=\"2\"];\n}\n",
+    ///         bigrams.to_dot(2)
+    ///     );
+    /// }
+    /// ```
+    pub fn to_dot(&self, min_count: C) -> String {
+        let mut edges: Vec<_> = self.0.iter().filter(|e| *e.1 >= min_count).collect();
+        edges.sort_by(|a, b| a.0.cmp(b.0));
+        let mut dot = String::from("digraph {\n");
+        for ((from, to), count) in edges {
+            dot.push_str(&format!(
+                "    {} -> {} [label=\"{}\"];\n",
+                dot_id(from),
+                dot_id(to),
+                count
+            ));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Quotes `label` as a DOT identifier.
+fn dot_id<T: fmt::Display>(label: &T) -> String {
+    let label = label.to_string();
+    let mut id = String::with_capacity(label.len() + 2);
+    id.push('"');
+    for c in label.chars() {
+        if c == '"' || c == '\\' {
+            id.push('\\');
+        }
+        id.push(c);
+    }
+    id.push('"');
+    id
+}
+
+fn write_entries<'a, T, C, I>(f: &mut fmt::Formatter, entries: I) -> fmt::Result
+where
+    T: fmt::Display + 'a,
+    C: fmt::Display + 'a,
+    I: Iterator<Item = (&'a T, &'a C)>,
+{
+    f.write_str("{")?;
+    for (i, (elem, count)) in entries.enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{}: {}", elem, count)?;
+    }
+    f.write_str("}")
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn display() {
+        assert_eq!("{x: 2}", bagof!("x", "x").to_string());
+        assert_eq!("{}", ::Bag::<u8>::new().to_string());
+    }
+
+    #[test]
+    fn histogram_labels_align() {
+        let bag = bagof!("long", "x", "x", "x", "x");
+        assert_eq!("x    | #### 4\nlong | # 1\n", bag.render_histogram(4));
+        assert_eq!("", ::Bag::<u8>::new().render_histogram(4));
+    }
+
+    #[test]
+    fn histogram_without_bars() {
+        let bag = bagof!('a', 'a', 'b');
+        assert_eq!("a | 2\nb | 1\n", bag.render_histogram(0));
+        assert_eq!("a | 2\n", bag.histogram(0).top(1).to_string());
+    }
+
+    #[test]
+    fn histogram_top() {
+        let bag = bagof!('a', 'a', 'a', 'b', 'b', 'c');
+        assert_eq!("a | ### 3\nb | ## 2\n", bag.histogram(3).top(2).to_string());
+        assert_eq!("", bag.histogram(3).top(0).to_string());
+        assert_eq!(
+            bag.render_histogram(3),
+            bag.histogram(3).top(10).to_string()
+        );
+    }
+
+    #[test]
+    fn dot_quotes_labels() {
+        let bigrams = bagof!(("say \"hi\"", "ok"), ("a", "b"));
+        assert_eq!(
+            "digraph {\n    \"a\" -> \"b\" [label=\"1\"];\n    \"say \\\"hi\\\"\" -> \"ok\" [label=\"1\"];\n}\n",
+            bigrams.to_dot(1)
+        );
+        assert_eq!("digraph {\n}\n", bigrams.to_dot(2));
+    }
+}
@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use {Bag, Count};
+
+/// A discrete probability distribution over elements, produced by
+/// `Bag::normalize`.
+///
+/// Probabilities are the relative frequencies of the bag and sum to `1.0`,
+/// up to rounding; the distribution of an empty bag is empty.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Distribution<T: Eq + Hash>(HashMap<T, f64>);
+
+impl<T: Eq + Hash> Distribution<T> {
+    /// Returns the probability of `elem`, zero when it was never seen.
+    pub fn probability(&self, elem: &T) -> f64 {
+        self.0.get(elem).map_or(0.0, |&p| p)
+    }
+
+    /// Returns the number of elements with non-zero probability.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a T, f64)> + 'a {
+        self.0.iter().map(|(elem, &p)| (elem, p))
+    }
+}
+
+impl<T: Eq + Hash + Clone, C: Count> Bag<T, C> {
+    /// Converts the counts into relative frequencies.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let dist = bagof!("a", "a", "a", "b").normalize();
+    ///     assert_eq!(0.75, dist.probability(&"a"));
+    ///     assert_eq!(0.25, dist.probability(&"b"));
+    ///     assert_eq!(0.0, dist.probability(&"c"));
+    /// }
+    /// ```
+    pub fn normalize(&self) -> Distribution<T> {
+        let total = self.len().to_f64();
+        Distribution(
+            self.0
+                .iter()
+                .map(|(elem, &count)| (elem.clone(), count.to_f64() / total))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use Bag;
+
+    #[test]
+    fn sums_to_one() {
+        let mut bag = Bag::new();
+        for i in 0..1000u32 {
+            bag.put(i % 7);
+        }
+        let dist = bag.normalize();
+        let total: f64 = dist.iter().map(|(_, p)| p).sum();
+        assert!((total - 1.0).abs() < 1e-12);
+        assert_eq!(7, dist.len());
+        assert!(Bag::<u32>::new().normalize().is_empty());
+    }
+}
@@ -0,0 +1,86 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// The error returned when a count does not fit in its count type.
+///
+/// Zero-sized and `Copy`, so the fallible `try_*` and `checked_*` variants
+/// cost no more than the panicking ones on the success path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OverflowError;
+
+impl fmt::Display for OverflowError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("count overflow")
+    }
+}
+
+impl Error for OverflowError {}
+
+/// The error returned when removing more occurrences than a bag holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UnderflowError;
+
+impl fmt::Display for UnderflowError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("count underflow")
+    }
+}
+
+impl Error for UnderflowError {}
+
+/// The error returned by `Bag::from_frequencies` for an invalid frequency
+/// table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FrequencyError {
+    /// An element was listed with a count of zero.
+    ZeroCount,
+    /// The counts of a repeated element add up to more than the count type
+    /// holds.
+    Overflow,
+}
+
+impl fmt::Display for FrequencyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FrequencyError::ZeroCount => f.write_str("zero count in frequency table"),
+            FrequencyError::Overflow => f.write_str("count overflow"),
+        }
+    }
+}
+
+impl Error for FrequencyError {}
+
+/// The error returned when reading a frequency table fails.
+#[derive(Debug)]
+pub enum TableError {
+    /// The reader failed.
+    Io(io::Error),
+    /// The given line, counting from 1, is not a valid `element,count`
+    /// pair with a positive count, or repeats an element past overflow.
+    InvalidLine(usize),
+}
+
+impl fmt::Display for TableError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TableError::Io(ref err) => write!(f, "read failed: {}", err),
+            TableError::InvalidLine(line) => write!(f, "invalid frequency on line {}", line),
+        }
+    }
+}
+
+impl Error for TableError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            TableError::Io(ref err) => Some(err),
+            TableError::InvalidLine(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for TableError {
+    fn from(err: io::Error) -> Self {
+        TableError::Io(err)
+    }
+}
@@ -0,0 +1,36 @@
+use std::hash::Hash;
+
+use {Bag, Count};
+
+/// Counts the items of any iterator without naming the bag type.
+///
+/// # Examples
+///
+/// ```
+/// use bag::IteratorBagExt;
+/// let words = "a rose is a rose".split(' ').collect_bag();
+/// assert_eq!(2, words.occurrence(&"rose"));
+///
+/// let bytes = b"abracadabra".iter().count_occurrences::<u8>();
+/// assert_eq!(5u8, bytes.occurrence(&&b'a'));
+/// ```
+pub trait IteratorBagExt: Iterator + Sized
+where
+    Self::Item: Eq + Hash,
+{
+    /// Counts the items into a `Bag`.
+    fn collect_bag(self) -> Bag<Self::Item> {
+        self.collect()
+    }
+
+    /// Counts the items into a `Bag` with counts of type `C`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a count overflows `C` in debug builds; see `Bag::put`.
+    fn count_occurrences<C: Count>(self) -> Bag<Self::Item, C> {
+        self.collect()
+    }
+}
+
+impl<I: Iterator> IteratorBagExt for I where I::Item: Eq + Hash {}
@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// An element reported by `HeavyHitters::top`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeavyHitter<'a, T: 'a> {
+    pub elem: &'a T,
+    /// The estimated count, never below the true count.
+    pub count: usize,
+    /// The most `count` can exceed the true count by.
+    pub error: usize,
+}
+
+impl<'a, T> HeavyHitter<'a, T> {
+    /// Returns the count the element is known to have reached.
+    pub fn guaranteed(&self) -> usize {
+        self.count - self.error
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Slot<T> {
+    elem: T,
+    count: usize,
+    error: usize,
+}
+
+/// Finds the most frequent elements of a stream while monitoring at most
+/// `capacity` of them, using the space-saving algorithm.
+///
+/// Every element occurring more than `len / capacity` times is monitored,
+/// and each count overestimates the truth by at most its `error`.
+#[derive(Debug, Clone)]
+pub struct HeavyHitters<T: Eq + Hash + Clone> {
+    capacity: usize,
+    total: usize,
+    // A binary min-heap on `count`, so the eviction candidate is the root.
+    heap: Vec<Slot<T>>,
+    positions: HashMap<T, usize>,
+}
+
+impl<T: Eq + Hash + Clone> HeavyHitters<T> {
+    /// Creates an empty summary monitoring up to `capacity` elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must be positive");
+        HeavyHitters {
+            capacity,
+            total: 0,
+            heap: Vec::with_capacity(capacity),
+            positions: HashMap::with_capacity(capacity),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Counts all the occurrences inserted, exactly.
+    pub fn len(&self) -> usize {
+        self.total
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total == 0
+    }
+
+    /// Insert an element.
+    ///
+    /// When the summary is full, an unmonitored element replaces the least
+    /// frequent monitored one and inherits its count as error.
+    pub fn put(&mut self, elem: T) {
+        self.total += 1;
+        if let Some(&i) = self.positions.get(&elem) {
+            self.heap[i].count += 1;
+            self.sift_down(i);
+        } else if self.heap.len() < self.capacity {
+            let i = self.heap.len();
+            self.positions.insert(elem.clone(), i);
+            self.heap.push(Slot {
+                elem,
+                count: 1,
+                error: 0,
+            });
+            self.sift_up(i);
+        } else {
+            let min = self.heap[0].count;
+            let old = std::mem::replace(
+                &mut self.heap[0],
+                Slot {
+                    elem: elem.clone(),
+                    count: min + 1,
+                    error: min,
+                },
+            );
+            self.positions.remove(&old.elem);
+            self.positions.insert(elem, 0);
+            self.sift_down(0);
+        }
+    }
+
+    /// Returns the estimated count of `elem` and its maximum error, or
+    /// `None` if it is not monitored.
+    pub fn occurrence(&self, elem: &T) -> Option<(usize, usize)> {
+        self.positions.get(elem).map(|&i| {
+            let slot = &self.heap[i];
+            (slot.count, slot.error)
+        })
+    }
+
+    /// Returns the `k` monitored elements with the highest estimated
+    /// counts, most frequent first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::HeavyHitters;
+    /// let mut hitters = HeavyHitters::new(2);
+    /// for word in "a b a c a b d a".split(' ') {
+    ///     hitters.put(word);
+    /// }
+    /// let top = hitters.top(1);
+    /// assert_eq!(&"a", top[0].elem);
+    /// assert_eq!(4, top[0].guaranteed());
+    /// ```
+    pub fn top(&self, k: usize) -> Vec<HeavyHitter<'_, T>> {
+        let mut top: Vec<_> = self
+            .heap
+            .iter()
+            .map(|slot| HeavyHitter {
+                elem: &slot.elem,
+                count: slot.count,
+                error: slot.error,
+            })
+            .collect();
+        top.sort_by(|a, b| b.count.cmp(&a.count).then(a.error.cmp(&b.error)));
+        top.truncate(k);
+        top
+    }
+
+    fn sift_up(&mut self, mut i: usize) {
+        while i > 0 {
+            let parent = (i - 1) / 2;
+            if self.heap[parent].count <= self.heap[i].count {
+                break;
+            }
+            self.swap(i, parent);
+            i = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut i: usize) {
+        loop {
+            let mut least = i;
+            for child in [2 * i + 1, 2 * i + 2] {
+                if child < self.heap.len() && self.heap[child].count < self.heap[least].count {
+                    least = child;
+                }
+            }
+            if least == i {
+                return;
+            }
+            self.swap(i, least);
+            i = least;
+        }
+    }
+
+    fn swap(&mut self, i: usize, j: usize) {
+        self.heap.swap(i, j);
+        *self
+            .positions
+            .get_mut(&self.heap[i].elem)
+            .expect("monitored") = i;
+        *self
+            .positions
+            .get_mut(&self.heap[j].elem)
+            .expect("monitored") = j;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HeavyHitters;
+    use Bag;
+
+    #[test]
+    fn bounds_hold_against_exact_counts() {
+        let mut hitters = HeavyHitters::new(20);
+        let mut exact = Bag::new();
+        for i in 0..10_000u32 {
+            // A skewed stream: small values are much more common.
+            let elem = (i * 7919) % 1000 % (1 + i % 50);
+            hitters.put(elem);
+            exact.put(elem);
+        }
+        assert_eq!(exact.len(), hitters.len());
+        for hitter in hitters.top(20) {
+            let truth = exact.occurrence(hitter.elem);
+            assert!(hitter.guaranteed() <= truth && truth <= hitter.count);
+        }
+        let threshold = exact.len() / hitters.capacity();
+        for (elem, &count) in exact.frequency() {
+            if count > threshold {
+                assert!(hitters.occurrence(elem).is_some());
+            }
+        }
+    }
+}
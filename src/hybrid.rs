@@ -0,0 +1,183 @@
+use std::hash::Hash;
+
+use sketch::CountMinSketch;
+use Bag;
+
+/// The answer to a `HybridBag` query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Estimate {
+    /// The count is exact.
+    Exact(usize),
+    /// The count comes from the sketch and may be an overestimate.
+    Approximate(usize),
+}
+
+impl Estimate {
+    /// Returns the count, exact or not.
+    pub fn count(self) -> usize {
+        match self {
+            Estimate::Exact(count) | Estimate::Approximate(count) => count,
+        }
+    }
+
+    pub fn is_exact(self) -> bool {
+        match self {
+            Estimate::Exact(_) => true,
+            Estimate::Approximate(_) => false,
+        }
+    }
+}
+
+/// A bag that counts exactly up to a limit of distinct elements, then
+/// degrades gracefully into a count-min sketch.
+///
+/// When a new element would exceed the limit, the less frequent half of the
+/// exact elements is moved into the sketch and the heavy hitters stay exact.
+/// From then on, only those heavy hitters are counted exactly; every other
+/// element, new or spilled, is counted by the sketch.
+#[derive(Debug, Clone)]
+pub struct HybridBag<T: Eq + Hash> {
+    exact: Bag<T>,
+    sketch: Option<CountMinSketch<T>>,
+    limit: usize,
+    width: usize,
+    depth: usize,
+}
+
+impl<T: Eq + Hash> HybridBag<T> {
+    /// Creates an empty bag keeping at most `limit` distinct elements exact,
+    /// spilling into a sketch of `depth` rows of `width` counters.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` or `depth` is zero.
+    pub fn new(limit: usize, width: usize, depth: usize) -> Self {
+        assert!(width > 0 && depth > 0, "sketch dimensions must be positive");
+        HybridBag {
+            exact: Bag::with_capacity(limit),
+            sketch: None,
+            limit,
+            width,
+            depth,
+        }
+    }
+
+    /// Returns `true` once the bag has started counting in the sketch.
+    pub fn is_spilled(&self) -> bool {
+        self.sketch.is_some()
+    }
+
+    /// Insert an element.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::{Estimate, HybridBag};
+    /// let mut bag = HybridBag::new(2, 256, 4);
+    /// for word in "a a a b b c".split(' ') {
+    ///     bag.put(word);
+    /// }
+    /// assert!(bag.is_spilled());
+    /// assert_eq!(Estimate::Exact(3), bag.occurrence(&"a"));
+    /// assert!(!bag.occurrence(&"c").is_exact());
+    /// assert!(bag.occurrence(&"c").count() >= 1);
+    /// ```
+    pub fn put(&mut self, elem: T) {
+        if let Some(count) = self.exact.0.get_mut(&elem) {
+            *count += 1;
+            return;
+        }
+        if self.sketch.is_none() && self.exact.distinct_len() < self.limit {
+            self.exact.put(elem);
+            return;
+        }
+        self.sketch_mut().put(&elem);
+    }
+
+    /// Counts the occurrences of `elem`, reporting whether the count is
+    /// exact.
+    pub fn occurrence(&self, elem: &T) -> Estimate {
+        match (self.exact.0.get(elem), &self.sketch) {
+            (Some(&count), _) => Estimate::Exact(count),
+            (None, Some(sketch)) => Estimate::Approximate(sketch.occurrence(elem)),
+            (None, None) => Estimate::Exact(0),
+        }
+    }
+
+    /// Counts all the elements, exactly.
+    pub fn len(&self) -> usize {
+        self.exact.len() + self.sketch.as_ref().map_or(0, |sketch| sketch.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the elements that are still counted exactly.
+    pub fn exact(&self) -> &Bag<T> {
+        &self.exact
+    }
+
+    /// Returns the sketch, creating it on first use by spilling the less
+    /// frequent half of the exact elements into it.
+    fn sketch_mut(&mut self) -> &mut CountMinSketch<T> {
+        if self.sketch.is_none() {
+            let mut sketch = CountMinSketch::new(self.width, self.depth);
+            let keep = self.exact.distinct_len().div_ceil(2);
+            if keep < self.exact.distinct_len() {
+                // Ties at the cutoff are kept or spilled arbitrarily, so
+                // exactly `keep` elements stay exact.
+                let mut entries: Vec<(T, usize)> = self.exact.0.drain().collect();
+                if keep > 0 {
+                    entries.select_nth_unstable_by(keep - 1, |a, b| b.1.cmp(&a.1));
+                }
+                for (elem, count) in entries.drain(keep..) {
+                    sketch.add(&elem, count);
+                }
+                self.exact.0.extend(entries);
+            }
+            self.sketch = Some(sketch);
+        }
+        self.sketch.as_mut().expect("sketch was just created")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Estimate, HybridBag};
+
+    #[test]
+    fn spills_the_tail() {
+        let mut bag = HybridBag::new(4, 1024, 4);
+        for (elem, n) in [('a', 10), ('b', 8), ('c', 2), ('d', 1)].iter().cloned() {
+            for _ in 0..n {
+                bag.put(elem);
+            }
+        }
+        assert!(!bag.is_spilled());
+        assert_eq!(Estimate::Exact(2), bag.occurrence(&'c'));
+
+        bag.put('e');
+        assert!(bag.is_spilled());
+        assert_eq!(Estimate::Exact(10), bag.occurrence(&'a'));
+        assert_eq!(Estimate::Exact(8), bag.occurrence(&'b'));
+        assert!(bag.occurrence(&'c').count() >= 2);
+        assert!(!bag.occurrence(&'e').is_exact());
+        assert_eq!(22, bag.len());
+    }
+
+    #[test]
+    fn uniform_counts_keep_half() {
+        let mut bag = HybridBag::new(4, 1024, 4);
+        for c in "abcde".chars() {
+            bag.put(c);
+        }
+        assert_eq!(2, bag.exact().distinct_len());
+        let &kept = bag.exact().distinct().next().unwrap();
+        for _ in 0..100 {
+            bag.put(kept);
+        }
+        assert_eq!(Estimate::Exact(101), bag.occurrence(&kept));
+        assert_eq!(105, bag.len());
+    }
+}
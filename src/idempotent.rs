@@ -0,0 +1,142 @@
+use std::collections::{HashSet, VecDeque};
+use std::hash::Hash;
+
+use Bag;
+
+/// A `Bag` whose mutations are tagged with operation ids, so that replaying
+/// an operation is a no-op.
+///
+/// Only the most recent `window` ids are remembered; an id that has fallen
+/// out of the window is applied again. Size the window to cover the
+/// redelivery horizon of the message source.
+#[derive(Debug, Clone)]
+pub struct IdempotentBag<T: Eq + Hash, Id: Eq + Hash + Clone> {
+    bag: Bag<T>,
+    seen: HashSet<Id>,
+    order: VecDeque<Id>,
+    window: usize,
+}
+
+impl<T: Eq + Hash, Id: Eq + Hash + Clone> IdempotentBag<T, Id> {
+    /// Creates an empty bag remembering up to `window` recent operation ids.
+    pub fn new(window: usize) -> Self {
+        IdempotentBag {
+            bag: Bag::new(),
+            seen: HashSet::with_capacity(window),
+            order: VecDeque::with_capacity(window),
+            window,
+        }
+    }
+
+    /// Inserts `elem` under operation `id`, returning `false` without
+    /// changing the bag if `id` was already applied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::IdempotentBag;
+    /// let mut bag = IdempotentBag::new(16);
+    /// assert!(bag.put(1, "a"));
+    /// assert!(!bag.put(1, "a"));
+    /// assert!(bag.put(2, "a"));
+    /// assert_eq!(2, bag.occurrence(&"a"));
+    /// ```
+    pub fn put(&mut self, id: Id, elem: T) -> bool {
+        if !self.remember(id) {
+            return false;
+        }
+        self.bag.put(elem);
+        true
+    }
+
+    /// Removes one occurrence of `elem` under operation `id`, returning
+    /// `false` without changing the bag if `id` was already applied.
+    ///
+    /// Removing an absent element still consumes `id`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::IdempotentBag;
+    /// let mut bag = IdempotentBag::new(16);
+    /// bag.put(1, "a");
+    /// bag.put(2, "a");
+    /// assert!(bag.remove(3, &"a"));
+    /// assert!(!bag.remove(3, &"a"));
+    /// assert_eq!(1, bag.occurrence(&"a"));
+    /// ```
+    pub fn remove(&mut self, id: Id, elem: &T) -> bool {
+        if !self.remember(id) {
+            return false;
+        }
+        let emptied = match self.bag.0.get_mut(elem) {
+            Some(count) => {
+                *count -= 1;
+                *count == 0
+            }
+            None => false,
+        };
+        if emptied {
+            self.bag.0.remove(elem);
+        }
+        true
+    }
+
+    /// Returns `true` if `id` is still remembered as applied.
+    pub fn is_applied(&self, id: &Id) -> bool {
+        self.seen.contains(id)
+    }
+
+    /// Counts the occurrences of `elem`.
+    pub fn occurrence(&self, elem: &T) -> usize {
+        self.bag.occurrence(elem)
+    }
+
+    /// Returns the underlying bag.
+    pub fn bag(&self) -> &Bag<T> {
+        &self.bag
+    }
+
+    /// Consumes the wrapper, returning the underlying bag.
+    pub fn into_bag(self) -> Bag<T> {
+        self.bag
+    }
+
+    /// Records `id`, evicting the oldest one beyond the window. Returns
+    /// `false` if `id` was already recorded.
+    fn remember(&mut self, id: Id) -> bool {
+        if self.window == 0 {
+            return true;
+        }
+        if !self.seen.insert(id.clone()) {
+            return false;
+        }
+        self.order.push_back(id);
+        if self.order.len() > self.window {
+            if let Some(old) = self.order.pop_front() {
+                self.seen.remove(&old);
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IdempotentBag;
+
+    #[test]
+    fn window_eviction() {
+        let mut bag = IdempotentBag::new(2);
+        assert!(bag.put(1, 'x'));
+        assert!(bag.put(2, 'x'));
+        assert!(bag.put(3, 'x'));
+        assert!(!bag.is_applied(&1));
+        assert!(bag.is_applied(&3));
+
+        // id 1 fell out of the window, so it is applied again.
+        assert!(bag.put(1, 'x'));
+        assert!(!bag.put(3, 'x'));
+        assert_eq!(4, bag.occurrence(&'x'));
+    }
+}
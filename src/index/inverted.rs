@@ -0,0 +1,204 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use corpus::Corpus;
+use Bag;
+
+/// An inverted index mapping every element to the documents containing it,
+/// with its count in each.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use(bagof)]
+/// extern crate bag;
+/// use bag::InvertedIndex;
+/// fn main() {
+///     let mut index = InvertedIndex::new();
+///     index.insert(1, &bagof!("the", "cat", "sat"));
+///     index.insert(2, &bagof!("the", "dog", "sat", "sat"));
+///     index.insert(3, &bagof!("the", "end"));
+///
+///     assert_eq!(&[(1, 1), (2, 2)], index.postings(&"sat"));
+///     assert_eq!(vec![2], index.all_of(&["the", "dog"]));
+///     assert_eq!(vec![1, 2], index.any_of(&["cat", "dog"]));
+///     assert_eq!(2, index.ranked(&bagof!("sat"))[0].0);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct InvertedIndex<E: Eq + Hash, Id: Eq + Hash + Clone> {
+    postings: HashMap<E, Vec<(Id, usize)>>,
+    /// The documents in insertion order.
+    docs: Vec<Id>,
+    /// The insertion position of every document.
+    positions: HashMap<Id, usize>,
+}
+
+impl<E: Eq + Hash, Id: Eq + Hash + Clone> Default for InvertedIndex<E, Id> {
+    fn default() -> Self {
+        InvertedIndex {
+            postings: HashMap::new(),
+            docs: Vec::new(),
+            positions: HashMap::new(),
+        }
+    }
+}
+
+impl<E: Eq + Hash + Clone, Id: Eq + Hash + Clone> InvertedIndex<E, Id> {
+    /// Creates an empty index.
+    pub fn new() -> Self {
+        InvertedIndex::default()
+    }
+
+    /// Adds a posting for every element of `doc` under `id`.
+    ///
+    /// Postings are appended in insertion order, so documents come back
+    /// from queries in the order they were indexed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is already indexed.
+    pub fn insert(&mut self, id: Id, doc: &Bag<E>) {
+        assert!(
+            !self.positions.contains_key(&id),
+            "document is already indexed"
+        );
+        self.positions.insert(id.clone(), self.docs.len());
+        self.docs.push(id.clone());
+        for (elem, &count) in &doc.0 {
+            self.postings
+                .entry(elem.clone())
+                .or_default()
+                .push((id.clone(), count));
+        }
+    }
+}
+
+impl<E: Eq + Hash, Id: Eq + Hash + Clone> InvertedIndex<E, Id> {
+    /// Returns the number of documents indexed.
+    pub fn len(&self) -> usize {
+        self.docs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.docs.is_empty()
+    }
+
+    /// Returns the documents containing `elem` with its count in each.
+    pub fn postings(&self, elem: &E) -> &[(Id, usize)] {
+        self.postings
+            .get(elem)
+            .map_or(&[], |postings| &postings[..])
+    }
+
+    /// Returns the documents containing every one of `elems`, in indexing
+    /// order. No elements match every document.
+    pub fn all_of(&self, elems: &[E]) -> Vec<Id> {
+        let mut lists: Vec<&[(Id, usize)]> = elems.iter().map(|e| self.postings(e)).collect();
+        if lists.is_empty() {
+            return self.docs.clone();
+        }
+        // Filter the shortest list by membership in the others.
+        lists.sort_by_key(|list| list.len());
+        let others: Vec<HashSet<&Id>> = lists[1..]
+            .iter()
+            .map(|list| list.iter().map(|(id, _)| id).collect())
+            .collect();
+        lists[0]
+            .iter()
+            .filter(|(id, _)| others.iter().all(|set| set.contains(id)))
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
+    /// Returns the documents containing at least one of `elems`, each once,
+    /// in the order they are first found.
+    pub fn any_of(&self, elems: &[E]) -> Vec<Id> {
+        let mut seen = HashSet::new();
+        let mut ids = Vec::new();
+        for elem in elems {
+            for (id, _) in self.postings(elem) {
+                if seen.insert(id) {
+                    ids.push(id.clone());
+                }
+            }
+        }
+        ids
+    }
+
+    /// Ranks the documents sharing an element with `query` by TF-IDF,
+    /// highest score first, breaking ties by indexing order.
+    ///
+    /// Each shared element contributes its count in the query times its
+    /// count in the document times `ln(N / df)`, as in `Corpus::idf`.
+    pub fn ranked(&self, query: &Bag<E>) -> Vec<(Id, f64)> {
+        let n = self.docs.len() as f64;
+        let mut scores: HashMap<&Id, f64> = HashMap::new();
+        for (elem, &weight) in &query.0 {
+            let postings = self.postings(elem);
+            if postings.is_empty() {
+                continue;
+            }
+            let idf = (n / postings.len() as f64).ln();
+            for (id, count) in postings {
+                *scores.entry(id).or_insert(0.0) += (weight * count) as f64 * idf;
+            }
+        }
+        let mut ranked: Vec<(Id, f64)> = scores
+            .into_iter()
+            .map(|(id, score)| (id.clone(), score))
+            .collect();
+        ranked.sort_by(|a, b| {
+            b.1.partial_cmp(&a.1)
+                .expect("score is not NaN")
+                .then_with(|| self.positions[&a.0].cmp(&self.positions[&b.0]))
+        });
+        ranked
+    }
+}
+
+impl<E: Eq + Hash + Clone> Corpus<E> {
+    /// Builds an inverted index over the documents, keyed by name.
+    pub fn inverted_index(&self) -> InvertedIndex<E, String> {
+        let mut index = InvertedIndex::new();
+        for (name, doc) in self.documents() {
+            index.insert(name.to_string(), doc);
+        }
+        index
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InvertedIndex;
+    use corpus::Corpus;
+
+    #[test]
+    fn from_corpus() {
+        let mut corpus = Corpus::new();
+        corpus.insert("a", bagof!('x', 'y'));
+        corpus.insert("b", bagof!('y', 'z', 'z'));
+        let index = corpus.inverted_index();
+        assert_eq!(2, index.len());
+        assert_eq!(vec!["b".to_string()], index.all_of(&['y', 'z']));
+        assert_eq!(&[("b".to_string(), 2)], index.postings(&'z'));
+        assert!(index.postings(&'w').is_empty());
+        assert_eq!(2, index.all_of(&[]).len());
+    }
+
+    #[test]
+    fn indexing_order() {
+        let mut index = InvertedIndex::new();
+        for id in (0..20).rev() {
+            index.insert(id, &bagof!('x'));
+        }
+        let order: Vec<u32> = (0..20).rev().collect();
+        assert_eq!(order, index.all_of(&[]));
+        let ranked: Vec<u32> = index
+            .ranked(&bagof!('x'))
+            .into_iter()
+            .map(|r| r.0)
+            .collect();
+        assert_eq!(order, ranked);
+    }
+}
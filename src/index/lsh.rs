@@ -0,0 +1,138 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+use minhash::MinHashSignature;
+use {Bag, Count};
+
+/// An approximate nearest-neighbor index over bags, using MinHash
+/// signatures split into bands (locality-sensitive hashing).
+///
+/// Two bags with Jaccard similarity `s` share at least one band, and so
+/// find each other, with probability `1 - (1 - s^rows)^bands`. More rows
+/// per band make matches stricter; more bands make them more likely.
+#[derive(Debug, Clone)]
+pub struct LshIndex<T, Id: Eq + Hash + Clone> {
+    bands: usize,
+    rows: usize,
+    buckets: Vec<HashMap<u64, Vec<Id>>>,
+    signatures: HashMap<Id, MinHashSignature>,
+    marker: PhantomData<fn(&T)>,
+}
+
+impl<T: Eq + Hash, Id: Eq + Hash + Clone> LshIndex<T, Id> {
+    /// Creates an empty index hashing signatures of `bands * rows` values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bands` or `rows` is zero.
+    pub fn new(bands: usize, rows: usize) -> Self {
+        assert!(bands > 0 && rows > 0, "bands and rows must be positive");
+        LshIndex {
+            bands,
+            rows,
+            buckets: (0..bands).map(|_| HashMap::new()).collect(),
+            signatures: HashMap::new(),
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns the number of documents indexed.
+    pub fn len(&self) -> usize {
+        self.signatures.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.signatures.is_empty()
+    }
+
+    /// Indexes `doc` under `id`, replacing any document already indexed
+    /// under it.
+    pub fn insert<C: Count>(&mut self, id: Id, doc: &Bag<T, C>) {
+        if let Some(old) = self.signatures.remove(&id) {
+            let bands: Vec<u64> = self.band_hashes(&old).collect();
+            for (band, bucket) in bands.into_iter().zip(&mut self.buckets) {
+                let emptied = match bucket.get_mut(&band) {
+                    Some(ids) => {
+                        ids.retain(|other| *other != id);
+                        ids.is_empty()
+                    }
+                    None => false,
+                };
+                if emptied {
+                    bucket.remove(&band);
+                }
+            }
+        }
+        let signature = doc.minhash(self.bands * self.rows);
+        for (band, bucket) in self.band_hashes(&signature).zip(&mut self.buckets) {
+            bucket.entry(band).or_insert_with(Vec::new).push(id.clone());
+        }
+        self.signatures.insert(id, signature);
+    }
+
+    /// Returns the ids of the documents sharing a band with `doc`, most
+    /// similar first by estimated Jaccard similarity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::{Bag, LshIndex};
+    /// let mut index = LshIndex::new(16, 4);
+    /// let a: Bag<u32> = (0..100).collect();
+    /// let b: Bag<u32> = (5..105).collect();
+    /// let c: Bag<u32> = (1000..1100).collect();
+    /// index.insert("a", &a);
+    /// index.insert("b", &b);
+    /// index.insert("c", &c);
+    /// assert_eq!(vec!["a", "b"], index.query(&a));
+    /// ```
+    pub fn query<C: Count>(&self, doc: &Bag<T, C>) -> Vec<Id> {
+        let signature = doc.minhash(self.bands * self.rows);
+        let mut seen = HashSet::new();
+        let mut candidates = Vec::new();
+        for (band, bucket) in self.band_hashes(&signature).zip(&self.buckets) {
+            for id in bucket.get(&band).into_iter().flatten() {
+                if seen.insert(id) {
+                    let similarity = signature.estimate_jaccard(&self.signatures[id]);
+                    candidates.push((similarity, id));
+                }
+            }
+        }
+        candidates.sort_by(|a, b| b.0.partial_cmp(&a.0).expect("similarity is not NaN"));
+        candidates.into_iter().map(|(_, id)| id.clone()).collect()
+    }
+
+    fn band_hashes<'a>(&self, signature: &'a MinHashSignature) -> impl Iterator<Item = u64> + 'a {
+        signature.values().chunks(self.rows).map(|band| {
+            let mut hasher = DefaultHasher::new();
+            band.hash(&mut hasher);
+            hasher.finish()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LshIndex;
+    use Bag;
+
+    #[test]
+    fn reinsert_replaces() {
+        let mut index = LshIndex::new(16, 4);
+        let a: Bag<u32> = (0..100).collect();
+        let c: Bag<u32> = (1000..1100).collect();
+        index.insert("x", &a);
+        index.insert("x", &c);
+        assert_eq!(1, index.len());
+        assert!(index.query(&a).is_empty());
+        assert_eq!(vec!["x"], index.query(&c));
+        index.insert("x", &c);
+        assert!(index
+            .buckets
+            .iter()
+            .flat_map(|b| b.values())
+            .all(|ids| ids.len() == 1));
+    }
+}
@@ -0,0 +1,7 @@
+mod inverted;
+#[cfg(feature = "minhash")]
+mod lsh;
+
+pub use self::inverted::InvertedIndex;
+#[cfg(feature = "minhash")]
+pub use self::lsh::LshIndex;
@@ -0,0 +1,139 @@
+use std::collections::hash_map::{HashMap, Iter, IterMut, Keys, Values};
+use std::hash::Hash;
+use std::slice;
+
+use Count;
+
+/// Mutable access to the counts of a `Bag`, created by `Bag::counts_mut`.
+///
+/// Counts can be changed freely through `iter_mut`; elements whose count
+/// was set to zero are removed from the bag when this guard is dropped.
+pub struct CountsMut<'a, T: Eq + Hash + 'a, C: Count + 'a> {
+    pub(crate) map: &'a mut HashMap<T, C>,
+}
+
+impl<'a, T: Eq + Hash, C: Count> CountsMut<'a, T, C> {
+    /// Iterates over the elements and their mutable counts.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T, C> {
+        self.map.iter_mut()
+    }
+}
+
+impl<'a, 'b, T: Eq + Hash, C: Count> IntoIterator for &'b mut CountsMut<'a, T, C> {
+    type Item = (&'b T, &'b mut C);
+    type IntoIter = IterMut<'b, T, C>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.map.iter_mut()
+    }
+}
+
+impl<'a, T: Eq + Hash, C: Count> Drop for CountsMut<'a, T, C> {
+    fn drop(&mut self) {
+        self.map.retain(|_, count| *count != C::ZERO);
+    }
+}
+
+/// An iterator over the elements of a `Bag` and their counts, created by
+/// `Bag::frequency`.
+#[derive(Debug, Clone)]
+pub struct Frequency<'a, T: 'a, C: 'a>(pub(crate) Iter<'a, T, C>);
+
+impl<'a, T, C> Iterator for Frequency<'a, T, C> {
+    type Item = (&'a T, &'a C);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, T, C> ExactSizeIterator for Frequency<'a, T, C> {}
+
+/// An iterator over the distinct elements of a `Bag`, created by
+/// `Bag::distinct`.
+#[derive(Debug, Clone)]
+pub struct Distinct<'a, T: 'a, C: 'a>(pub(crate) Keys<'a, T, C>);
+
+impl<'a, T, C> Iterator for Distinct<'a, T, C> {
+    type Item = &'a T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, T, C> ExactSizeIterator for Distinct<'a, T, C> {}
+
+/// An iterator over the counts of a `Bag`, created by `Bag::counts`.
+#[derive(Debug, Clone)]
+pub struct Counts<'a, T: 'a, C: 'a>(pub(crate) Values<'a, T, C>);
+
+impl<'a, T, C: Copy> Iterator for Counts<'a, T, C> {
+    type Item = C;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().copied()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, T, C: Copy> ExactSizeIterator for Counts<'a, T, C> {}
+
+/// An iterator over the elements of an `IndexBag` and their counts in
+/// insertion order, created by `IndexBag::frequency`.
+#[derive(Debug, Clone)]
+pub struct IndexFrequency<'a, T: 'a>(pub(crate) slice::Iter<'a, (T, usize)>);
+
+impl<'a, T> Iterator for IndexFrequency<'a, T> {
+    type Item = (&'a T, &'a usize);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(elem, count)| (elem, count))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IndexFrequency<'a, T> {}
+
+/// An iterator over the distinct elements of an `IndexBag` in insertion
+/// order, created by `IndexBag::distinct`.
+#[derive(Debug, Clone)]
+pub struct IndexDistinct<'a, T: 'a>(pub(crate) slice::Iter<'a, (T, usize)>);
+
+impl<'a, T> Iterator for IndexDistinct<'a, T> {
+    type Item = &'a T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(elem, _)| elem)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IndexDistinct<'a, T> {}
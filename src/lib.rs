@@ -1,22 +1,102 @@
-#![feature(conservative_impl_trait)]
-
-use std::collections::HashMap;
+use std::borrow::Borrow;
+use std::cmp::Reverse;
 use std::collections::hash_map::Entry::{Occupied, Vacant};
+use std::collections::{BinaryHeap, HashMap, TryReserveError};
 use std::hash::Hash;
+use std::ops::Index;
 
 /// An Unordered `MultiSet`.
+///
+/// Multiplicities are stored as `C`, which defaults to `usize`. Use
+/// `Bag::<T, u32>::default()` to count in a narrower (or wider) type.
+///
+/// Indexing a bag with a built-in count type returns the count of an
+/// element, zero when it is absent.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use(bagof)]
+/// extern crate bag;
+/// fn main() {
+///     let bag = bagof!(String::from("a"), String::from("a"));
+///     assert_eq!(2, bag["a"]);
+///     assert_eq!(0, bag["b"]);
+/// }
+/// ```
 #[derive(Debug, Clone, PartialEq)]
-pub struct Bag<T: Eq + Hash>(HashMap<T, usize>);
+pub struct Bag<T: Eq + Hash, C: Count = usize>(HashMap<T, C>);
 
-impl<T: Eq + Hash> Default for Bag<T> {
+impl<T: Eq + Hash, C: Count> Default for Bag<T, C> {
     fn default() -> Self {
         Bag(HashMap::new())
     }
 }
 
+/// Generates `Index` for the built-in count types, so a missing element can
+/// yield a reference to a static zero.
+macro_rules! impl_index {
+    ( $( $c:ty ),* ) => {
+        $(
+            /// Returns the count of an element, zero when it is absent.
+            impl<'a, T, Q> Index<&'a Q> for Bag<T, $c>
+            where
+                T: Eq + Hash + Borrow<Q>,
+                Q: Eq + Hash + ?Sized,
+            {
+                type Output = $c;
+
+                fn index(&self, elem: &Q) -> &$c {
+                    self.0.get(elem).unwrap_or(&0)
+                }
+            }
+        )*
+    };
+}
+
+impl_index!(u8, u16, u32, u64, u128, usize);
+
+/// Creates a `Bag` from a list of elements, `bagof!(a, b, a)`, from
+/// element-count entries, `bagof!{a => 2, b => 1}`, or from one repeated
+/// element, `bagof![a; 2]`.
+///
+/// Entries listing the same element add up, panicking if the sum overflows.
+/// An element given a count of zero is left out, so `bagof![a; 0]` is
+/// empty.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use(bagof)]
+/// extern crate bag;
+/// fn main() {
+///     let abracadabra = bagof!('a', 'b', 'r', 'a', 'c', 'a', 'd', 'a', 'b', 'r', 'a');
+///     assert_eq!(abracadabra, bagof!{'a' => 5, 'b' => 2, 'r' => 2, 'c' => 1, 'd' => 1, 'z' => 0});
+///     assert_eq!(bagof!('x', 'x', 'x'), bagof!['x'; 3]);
+/// }
+/// ```
 #[macro_export]
 macro_rules! bagof {
     () => { $crate::Bag::new() };
+    ( $( $item: expr => $count: expr ),+ $(,)* ) => {
+        {
+            let entries = ::std::iter::IntoIterator::into_iter([ $( ($item, $count) ),+ ]);
+            let bag: $crate::Bag<_, usize> =
+                match $crate::Bag::from_frequencies(entries.filter(|entry| entry.1 != 0)) {
+                    Ok(bag) => bag,
+                    Err(err) => panic!("bagof!: {}", err),
+                };
+            bag
+        }
+    };
+    ( $item: expr; $count: expr ) => {
+        {
+            let mut counts = ::std::collections::HashMap::with_capacity(1);
+            counts.insert($item, $count);
+            let bag: $crate::Bag<_, usize> = $crate::Bag::from(counts);
+            bag
+        }
+    };
     ( $( $item: expr ),* ) => {
         {
             let mut bag = $crate::Bag::new();
@@ -26,14 +106,235 @@ macro_rules! bagof {
     };
 }
 
+/// Counts the overlapping `n`-grams of the listed items as a `Bag<[T; n]>`;
+/// `n` must be a constant.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use(ngram, bigram)]
+/// extern crate bag;
+/// fn main() {
+///     let grams = ngram!(3; 'a', 'b', 'a', 'b');
+///     assert_eq!(1, grams.occurrence(&['a', 'b', 'a']));
+///     assert_eq!(2, bigram!('a', 'b', 'a', 'b').occurrence(&('a', 'b')));
+/// }
+/// ```
+#[macro_export]
+macro_rules! ngram {
+    ( $n: expr; $( $item: expr ),* ) => {
+        $crate::Bag::<[_; $n]>::from_ngrams([ $( $item ),* ])
+    };
+}
+
+/// Counts the adjacent pairs of the listed items as `(a, b)` tuples, the
+/// form taken by `collocations`, `MarkovModel` and `to_dot`.
+#[macro_export]
+macro_rules! bigram {
+    ( $( $item: expr ),* ) => {
+        $crate::Bag::from_skipgrams([ $( $item ),* ], 2, 0)
+    };
+}
+
+/// Counts the overlapping triples of the listed items; see `ngram!`.
+#[macro_export]
+macro_rules! trigram {
+    ( $( $item: expr ),* ) => {
+        $crate::Bag::<[_; 3]>::from_ngrams([ $( $item ),* ])
+    };
+}
+
+#[cfg(feature = "bench")]
+pub mod bench;
+pub mod collocations;
+mod composite;
+mod concurrent;
+mod convert;
+mod corpus;
+mod count;
+mod display;
+mod distribution;
+mod error;
+mod ext;
+mod heavy;
+mod hybrid;
+mod idempotent;
+mod index;
+mod iter;
+pub mod lm;
+mod markov;
+mod matrix;
+#[cfg(feature = "minhash")]
+mod minhash;
+pub mod ngram;
+mod ordered;
+mod privacy;
+mod query;
+mod random;
+mod remove;
+mod sample;
+mod setops;
+mod signed;
+mod simhash;
+mod sketch;
+mod smoothing;
+mod stats;
+mod table;
+#[cfg(feature = "tdigest")]
+mod tdigest;
+pub mod text;
+mod timeseries;
+mod transform;
+mod vocab;
+
+pub use composite::{CompositeKey, TupleField};
+pub use concurrent::ConcurrentBag;
+pub use corpus::Corpus;
+pub use count::Count;
+pub use display::{DisplaySorted, Histogram};
+pub use distribution::Distribution;
+pub use error::{FrequencyError, OverflowError, TableError, UnderflowError};
+pub use ext::IteratorBagExt;
+pub use heavy::{HeavyHitter, HeavyHitters};
+pub use hybrid::{Estimate, HybridBag};
+pub use idempotent::IdempotentBag;
+pub use index::InvertedIndex;
+#[cfg(feature = "minhash")]
+pub use index::LshIndex;
+pub use iter::{Counts, CountsMut, Distinct, Frequency, IndexDistinct, IndexFrequency};
+pub use markov::MarkovModel;
+pub use matrix::SparseMatrix;
+#[cfg(feature = "minhash")]
+pub use minhash::MinHashSignature;
+pub use ordered::IndexBag;
+pub use privacy::Suppression;
+pub use query::Query;
+pub use random::{RandomSource, SplitMix64};
+pub use remove::RemovePolicy;
+pub use sample::{Sampler, SamplingTable};
+pub use setops::{Difference, Intersection, Union};
+pub use signed::SignedBag;
+pub use simhash::hamming_distance;
+pub use sketch::{ApproxDistinct, CountMinSketch};
+pub use smoothing::SmoothedDistribution;
+#[cfg(feature = "tdigest")]
+pub use tdigest::{TDigest, ToF64};
+pub use timeseries::{RollupPolicy, Tier, TimeSeriesBag};
+pub use transform::{KeyTransform, Lowercase, TransformBag};
+pub use vocab::{Vocabulary, Weighting};
+
 impl<T: Eq + Hash> Bag<T> {
     /// Creates a new empty `Bag`.
     pub fn new() -> Self {
         Bag(HashMap::new())
     }
 
+    /// Creates an empty `Bag` with room for at least `capacity` distinct
+    /// elements before reallocating.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the allocation size overflows `usize`; see `try_reserve`
+    /// for a fallible alternative.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::Bag;
+    /// let bag: Bag<&str> = Bag::with_capacity(10);
+    /// assert!(bag.capacity() >= 10);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        Bag(HashMap::with_capacity(capacity))
+    }
+}
+
+impl<T: Eq + Hash, C: Count> Bag<T, C> {
+    /// Returns the number of distinct elements the bag can hold without
+    /// reallocating.
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    /// Reserves capacity for at least `additional` more distinct elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new allocation size overflows `usize`; use
+    /// `try_reserve` where panics are unacceptable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let mut bag = bagof!(1);
+    ///     bag.reserve(10);
+    ///     assert!(bag.capacity() >= 11);
+    /// }
+    /// ```
+    pub fn reserve(&mut self, additional: usize) {
+        self.0.reserve(additional)
+    }
+
+    /// Tries to reserve capacity for at least `additional` more distinct
+    /// elements, returning an error instead of panicking if the allocation
+    /// cannot be made.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::Bag;
+    /// let mut bag: Bag<u8> = Bag::new();
+    /// assert!(bag.try_reserve(16).is_ok());
+    /// assert!(bag.try_reserve(usize::max_value()).is_err());
+    /// ```
+    #[inline]
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.0.try_reserve(additional)
+    }
+
+    /// Shrinks the capacity of the bag as much as possible.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::Bag;
+    /// let mut bag = Bag::with_capacity(100);
+    /// bag.put(1);
+    /// bag.shrink_to_fit();
+    /// assert!(bag.capacity() >= 1);
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        self.0.shrink_to_fit()
+    }
+
+    /// Removes all elements, keeping the allocated capacity for reuse.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let mut bag = bagof!(1, 2, 2);
+    ///     let capacity = bag.capacity();
+    ///     bag.clear();
+    ///     assert!(bag.is_empty());
+    ///     assert_eq!(capacity, bag.capacity());
+    /// }
+    /// ```
+    pub fn clear(&mut self) {
+        self.0.clear()
+    }
+
     /// Counts all the elements, including each duplicate.
     ///
+    /// # Panics
+    ///
+    /// Panics in debug builds if the total overflows `C`; see `checked_len`.
+    ///
     /// # Examples
     ///
     /// ```
@@ -46,16 +347,62 @@ impl<T: Eq + Hash> Bag<T> {
     ///     assert_eq!(4, bag.len());
     /// }
     /// ```
-    pub fn len(&self) -> usize {
-        self.0.values().sum()
+    pub fn len(&self) -> C {
+        self.0.values().cloned().sum()
+    }
+
+    /// Counts all the elements like `len`, returning `None` if the total
+    /// overflows `C`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::Bag;
+    /// let mut bag = Bag::<char, u8>::default();
+    /// for _ in 0..100 {
+    ///     bag.put('a');
+    ///     bag.put('b');
+    /// }
+    /// assert_eq!(Some(200), bag.checked_len());
+    /// for _ in 0..100 {
+    ///     bag.put('c');
+    /// }
+    /// assert_eq!(None, bag.checked_len());
+    /// ```
+    #[inline]
+    pub fn checked_len(&self) -> Option<C> {
+        self.0
+            .values()
+            .try_fold(C::ZERO, |total, &count| total.checked_add(count))
+    }
+
+    /// Counts the distinct elements, ignoring duplicates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let bag = bagof!(1, 1, 2);
+    ///     assert_eq!(2, bag.distinct_len());
+    /// }
+    /// ```
+    pub fn distinct_len(&self) -> usize {
+        self.0.len()
     }
 
     pub fn is_empty(&self) -> bool {
-        self.len() == 0
+        self.0.is_empty()
     }
 
     /// Insert an element.
     ///
+    /// # Panics
+    ///
+    /// Panics if the count of `elem` overflows `C`, in every build profile.
+    /// See `try_put` and `saturating_put` to handle the limit instead.
+    ///
     /// # Examples
     ///
     /// ```
@@ -71,14 +418,99 @@ impl<T: Eq + Hash> Bag<T> {
     pub fn put(&mut self, elem: T) {
         match self.0.entry(elem) {
             Vacant(view) => {
-                view.insert(1);
+                view.insert(C::ONE);
             }
             Occupied(mut view) => {
-                *view.get_mut() += 1;
+                let count = view.get_mut();
+                *count = count
+                    .checked_add(C::ONE)
+                    .expect("count overflow; see try_put or saturating_put");
             }
         }
     }
 
+    /// Insert an occurrence of `elem`, only calling `make` to build the
+    /// owned element when it is not in the bag yet.
+    ///
+    /// Counting borrowed tokens into a `Bag<String>` this way allocates once
+    /// per distinct token instead of once per occurrence. Elements already
+    /// present are hashed once; new ones are hashed twice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::Bag;
+    /// let mut bag: Bag<String> = Bag::new();
+    /// let mut allocations = 0;
+    /// for word in "a b a a b".split(' ') {
+    ///     bag.put_with(word, || {
+    ///         allocations += 1;
+    ///         word.to_string()
+    ///     });
+    /// }
+    /// assert_eq!(3, bag.occurrence(&"a".to_string()));
+    /// assert_eq!(2, allocations);
+    /// ```
+    pub fn put_with<Q, F>(&mut self, elem: &Q, make: F)
+    where
+        T: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+        F: FnOnce() -> T,
+    {
+        if let Some(count) = self.0.get_mut(elem) {
+            *count += C::ONE;
+            return;
+        }
+        self.0.insert(make(), C::ONE);
+    }
+
+    /// Insert an element, returning its new count, or an error leaving the
+    /// bag unchanged if the count would overflow `C`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::{Bag, OverflowError};
+    /// let mut bag = Bag::<char, u8>::default();
+    /// for _ in 0..255 {
+    ///     bag.put('a');
+    /// }
+    /// assert_eq!(Err(OverflowError), bag.try_put('a'));
+    /// assert_eq!(255, bag.occurrence(&'a'));
+    /// assert_eq!(Ok(1), bag.try_put('b'));
+    /// ```
+    #[inline]
+    pub fn try_put(&mut self, elem: T) -> Result<C, OverflowError> {
+        match self.0.entry(elem) {
+            Vacant(view) => Ok(*view.insert(C::ONE)),
+            Occupied(mut view) => {
+                let count = view.get().checked_add(C::ONE).ok_or(OverflowError)?;
+                *view.get_mut() = count;
+                Ok(count)
+            }
+        }
+    }
+
+    /// Insert an element, leaving its count at the largest value of `C`
+    /// instead of overflowing. Returns the new count.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::Bag;
+    /// let mut bag = Bag::<char, u8>::default();
+    /// for _ in 0..300 {
+    ///     bag.saturating_put('a');
+    /// }
+    /// assert_eq!(255, bag.occurrence(&'a'));
+    /// ```
+    #[inline]
+    pub fn saturating_put(&mut self, elem: T) -> C {
+        let count = self.0.entry(elem).or_insert(C::ZERO);
+        *count = count.saturating_add(C::ONE);
+        *count
+    }
+
     /// Counts the occurrences of `value`.
     ///
     /// # Examples
@@ -93,16 +525,426 @@ impl<T: Eq + Hash> Bag<T> {
     ///     assert_eq!(0, bag.occurrence(&2));
     /// }
     /// ```
-    pub fn occurrence(&self, elem: &T) -> usize {
-        self.0.get(elem).map_or(0, |&x| x)
+    pub fn occurrence(&self, elem: &T) -> C {
+        self.0.get(elem).map_or(C::ZERO, |&x| x)
+    }
+
+    /// Returns `true` if the bag contains at least one `elem`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let bag = bagof!(String::from("a"));
+    ///     assert!(bag.contains("a"));
+    ///     assert!(!bag.contains("b"));
+    /// }
+    /// ```
+    pub fn contains<Q>(&self, elem: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.0.contains_key(elem)
+    }
+
+    pub fn frequency(&self) -> Frequency<'_, T, C> {
+        Frequency(self.0.iter())
+    }
+
+    pub fn distinct(&self) -> Distinct<'_, T, C> {
+        Distinct(self.0.keys())
+    }
+
+    /// Iterates over the counts alone, one per distinct element.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let bag = bagof!('a', 'b', 'a');
+    ///     let mut counts: Vec<usize> = bag.counts().collect();
+    ///     counts.sort();
+    ///     assert_eq!(vec![1, 2], counts);
+    ///     assert_eq!(2, bag.counts().len());
+    /// }
+    /// ```
+    pub fn counts(&self) -> Counts<'_, T, C> {
+        Counts(self.0.values())
+    }
+
+    /// Gives mutable access to the counts, so they can be rescaled or
+    /// clamped in place.
+    ///
+    /// Elements whose count is set to zero are removed once the returned
+    /// guard is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let mut bag = bagof!('a', 'a', 'a', 'a', 'b');
+    ///     for (_, count) in &mut bag.counts_mut() {
+    ///         *count /= 2;
+    ///     }
+    ///     assert_eq!(2, bag.occurrence(&'a'));
+    ///     assert!(!bag.contains(&'b'));
+    /// }
+    /// ```
+    pub fn counts_mut(&mut self) -> CountsMut<'_, T, C> {
+        CountsMut { map: &mut self.0 }
+    }
+
+    /// Adds every occurrence in `other` to this bag, moving its elements
+    /// instead of cloning them.
+    ///
+    /// The larger of the two maps is kept, so merging a small bag into a
+    /// large one only rehashes the small one.
+    ///
+    /// # Panics
+    ///
+    /// Panics in debug builds if a count overflows `C`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let mut bag = bagof!("a", "b");
+    ///     bag.merge(bagof!("a", "c"));
+    ///     assert_eq!(2, bag.occurrence(&"a"));
+    ///     assert_eq!(4, bag.len());
+    /// }
+    /// ```
+    pub fn merge(&mut self, mut other: Bag<T, C>) {
+        if other.0.len() > self.0.len() {
+            std::mem::swap(self, &mut other);
+        }
+        self.0.reserve(other.0.len());
+        for (elem, count) in other.0 {
+            *self.0.entry(elem).or_insert(C::ZERO) += count;
+        }
+    }
+
+    /// Multiplies every count by `factor`, as if the input had been seen
+    /// `factor` times over. Scaling by zero empties the bag.
+    ///
+    /// # Panics
+    ///
+    /// Panics in debug builds if a count overflows `C`; see `checked_scale`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let mut bag = bagof!('a', 'a', 'b');
+    ///     bag.scale(3);
+    ///     assert_eq!(6, bag.occurrence(&'a'));
+    ///     assert_eq!(9, bag.len());
+    /// }
+    /// ```
+    pub fn scale(&mut self, factor: C) {
+        if factor == C::ZERO {
+            self.0.clear();
+            return;
+        }
+        for count in self.0.values_mut() {
+            *count *= factor;
+        }
     }
 
-    pub fn frequency<'a>(&'a self) -> impl Iterator<Item = (&T, &usize)> + 'a {
-        self.0.iter()
+    /// Multiplies every count by `factor` like `scale`, or returns an error
+    /// leaving the bag unchanged if any count would overflow `C`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::{Bag, OverflowError};
+    /// let mut bag = Bag::<char, u8>::default();
+    /// for _ in 0..100 {
+    ///     bag.put('a');
+    /// }
+    /// assert_eq!(Ok(()), bag.checked_scale(2));
+    /// assert_eq!(Err(OverflowError), bag.checked_scale(2));
+    /// assert_eq!(200, bag.occurrence(&'a'));
+    /// ```
+    pub fn checked_scale(&mut self, factor: C) -> Result<(), OverflowError> {
+        if self
+            .0
+            .values()
+            .any(|&count| count.checked_mul(factor).is_none())
+        {
+            return Err(OverflowError);
+        }
+        self.scale(factor);
+        Ok(())
     }
 
-    pub fn distinct<'a>(&'a self) -> impl Iterator<Item = &T> + 'a {
-        self.0.keys()
+    /// Returns the element making up more than half of all occurrences, if
+    /// there is one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     assert_eq!(Some(&"yes"), bagof!("yes", "no", "yes").majority());
+    ///     assert_eq!(None, bagof!("yes", "no").majority());
+    /// }
+    /// ```
+    pub fn majority(&self) -> Option<&T> {
+        let total = self.len();
+        self.0
+            .iter()
+            .find(|&(_, &count)| count > total - count)
+            .map(|(elem, _)| elem)
+    }
+
+    /// Returns a most frequent element and its count, or `None` if the bag
+    /// is empty. When several elements tie, any one of them is returned; see
+    /// `modes`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// use bag::Bag;
+    /// fn main() {
+    ///     assert_eq!(Some((&'a', 2)), bagof!('a', 'b', 'a').mode());
+    ///     assert_eq!(None, Bag::<char>::new().mode());
+    /// }
+    /// ```
+    pub fn mode(&self) -> Option<(&T, C)> {
+        self.0
+            .iter()
+            .max_by_key(|&(_, &count)| count)
+            .map(|(elem, &count)| (elem, count))
+    }
+
+    /// Returns a most frequent element and its count; the same as `mode`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     assert_eq!(Some((&'a', 2)), bagof!('a', 'b', 'a').max_by_count());
+    /// }
+    /// ```
+    pub fn max_by_count(&self) -> Option<(&T, C)> {
+        self.mode()
+    }
+
+    /// Returns the highest count, or `None` if the bag is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// use bag::Bag;
+    /// fn main() {
+    ///     assert_eq!(Some(2), bagof!('a', 'b', 'a').max_count());
+    ///     assert_eq!(None, Bag::<char>::new().max_count());
+    /// }
+    /// ```
+    pub fn max_count(&self) -> Option<C> {
+        self.0.values().cloned().max()
+    }
+
+    /// Returns the lowest count, or `None` if the bag is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     assert_eq!(Some(1), bagof!('a', 'b', 'a').min_count());
+    /// }
+    /// ```
+    pub fn min_count(&self) -> Option<C> {
+        self.0.values().cloned().min()
+    }
+
+    /// Returns every element tied for the highest count.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let bag = bagof!('a', 'b', 'a', 'b', 'c');
+    ///     let mut modes = bag.modes();
+    ///     modes.sort();
+    ///     assert_eq!(vec![&'a', &'b'], modes);
+    /// }
+    /// ```
+    pub fn modes(&self) -> Vec<&T> {
+        let max = match self.mode() {
+            Some((_, max)) => max,
+            None => return Vec::new(),
+        };
+        self.0
+            .iter()
+            .filter(|&(_, &count)| count == max)
+            .map(|(elem, _)| elem)
+            .collect()
+    }
+
+    /// Returns the element with strictly more occurrences than any other, or
+    /// `None` if the bag is empty or the top count is tied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     assert_eq!(Some(&'a'), bagof!('a', 'a', 'b', 'c').plurality());
+    ///     assert_eq!(None, bagof!('a', 'a', 'b', 'b', 'c').plurality());
+    /// }
+    /// ```
+    pub fn plurality(&self) -> Option<&T> {
+        let mut best: Option<(&T, C)> = None;
+        let mut tied = false;
+        for (elem, &count) in &self.0 {
+            match best {
+                Some((_, max)) if count < max => {}
+                Some((_, max)) if count == max => tied = true,
+                _ => {
+                    best = Some((elem, count));
+                    tied = false;
+                }
+            }
+        }
+        if tied {
+            None
+        } else {
+            best.map(|(elem, _)| elem)
+        }
+    }
+
+    /// Returns the `k` most frequent elements by descending count, extended
+    /// with every element tied with the `k`-th.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let bag = bagof!('a', 'a', 'a', 'b', 'b', 'c', 'c', 'd');
+    ///     let top = bag.top_k_with_ties(2);
+    ///     assert_eq!(3, top.len());
+    ///     assert_eq!((&'a', 3), top[0]);
+    ///     assert!(top[1..].iter().all(|&(_, count)| count == 2));
+    /// }
+    /// ```
+    pub fn top_k_with_ties(&self, k: usize) -> Vec<(&T, C)> {
+        let mut elems: Vec<(&T, C)> = self.0.iter().map(|(e, &c)| (e, c)).collect();
+        elems.sort_by_key(|&(_, c)| Reverse(c));
+        if k == 0 {
+            elems.clear();
+        } else if k < elems.len() {
+            let cutoff = elems[k - 1].1;
+            let end = elems[k..]
+                .iter()
+                .position(|&(_, c)| c < cutoff)
+                .map_or(elems.len(), |i| k + i);
+            elems.truncate(end);
+        }
+        elems
+    }
+
+    /// Partitions the distinct elements into `k` groups whose total counts
+    /// are approximately equal.
+    ///
+    /// Elements are visited by descending count and each one is placed into
+    /// the group with the smallest total so far. Returns an empty `Vec` when
+    /// `k` is zero; some groups may be empty when `k` exceeds the number of
+    /// distinct elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let bag = bagof!("a", "a", "a", "b", "b", "c");
+    ///     let bins = bag.equal_mass_bins(2);
+    ///     let mass: Vec<usize> = bins
+    ///         .iter()
+    ///         .map(|bin| bin.iter().map(|e| bag.occurrence(e)).sum())
+    ///         .collect();
+    ///     assert_eq!(vec![3, 3], mass);
+    /// }
+    /// ```
+    pub fn equal_mass_bins(&self, k: usize) -> Vec<Vec<&T>> {
+        let mut bins = vec![Vec::new(); k];
+        for (elem, i) in self.lpt(k).0 {
+            bins[i].push(elem);
+        }
+        bins
+    }
+
+    /// Packs the distinct elements into `n_bins` bins weighted by their
+    /// counts, using the greedy longest-processing-time rule.
+    ///
+    /// Returns the bin index assigned to each element together with the
+    /// total load of every bin. With zero bins nothing is assigned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let bag = bagof!("a", "a", "a", "b", "b", "c");
+    ///     let (assignment, loads) = bag.pack_into(2);
+    ///     assert_eq!(vec![3, 3], loads);
+    ///     assert_eq!(assignment[&"b"], assignment[&"c"]);
+    ///     assert_ne!(assignment[&"a"], assignment[&"b"]);
+    /// }
+    /// ```
+    pub fn pack_into(&self, n_bins: usize) -> (HashMap<&T, usize>, Vec<C>) {
+        let (placements, loads) = self.lpt(n_bins);
+        (placements.into_iter().collect(), loads)
+    }
+
+    /// Assigns elements by descending count to the least loaded of `n` bins,
+    /// returning the placements in visiting order and the final loads.
+    fn lpt(&self, n: usize) -> (Vec<(&T, usize)>, Vec<C>) {
+        let mut loads = vec![C::ZERO; n];
+        if n == 0 {
+            return (Vec::new(), loads);
+        }
+        let mut elems: Vec<(&T, C)> = self.0.iter().map(|(e, &c)| (e, c)).collect();
+        elems.sort_by_key(|&(_, c)| Reverse(c));
+
+        let mut heap: BinaryHeap<Reverse<(C, usize)>> =
+            (0..n).map(|i| Reverse((C::ZERO, i))).collect();
+        let mut placements = Vec::with_capacity(elems.len());
+        for (elem, count) in elems {
+            let Reverse((load, i)) = heap.pop().expect("n > 0");
+            placements.push((elem, i));
+            loads[i] = load + count;
+            heap.push(Reverse((loads[i], i)));
+        }
+        (placements, loads)
     }
 }
 
@@ -126,4 +968,68 @@ mod tests {
         check_bagof!('a', 'b', 'r', 'a', 'c', 'a', 'd', 'a', 'b', 'r', 'a');
         check_bagof!("I", "am", "18", "years", "old", ".");
     }
+
+    #[test]
+    fn bagof_counts() {
+        let bag = bagof! {"a" => 3, "b" => 1, "a" => 1,};
+        assert_eq!(4, bag.occurrence(&"a"));
+        assert_eq!(5, bag.len());
+    }
+
+    #[test]
+    fn bagof_zero_count() {
+        assert!(bagof! {'a' => 0}.is_empty());
+        assert_eq!(bagof!['b'; 2], bagof! {'a' => 0, 'b' => 2});
+    }
+
+    #[test]
+    fn bagof_repeat() {
+        let bag = bagof![0u8; 1000];
+        assert_eq!(1000, bag.occurrence(&0));
+        assert_eq!(1, bag.distinct_len());
+        assert!(bagof!['a'; 0].is_empty());
+    }
+
+    #[test]
+    fn ngram_macros() {
+        let grams = trigram!("a", "rose", "is", "a", "rose");
+        assert_eq!(1, grams.occurrence(&["rose", "is", "a"]));
+        assert_eq!(3, grams.len());
+        assert_eq!(grams, ngram!(3; "a", "rose", "is", "a", "rose"));
+        assert!(bigram!(1).is_empty());
+        assert_eq!(bagof!((1, 2), (2, 1)), bigram!(1, 2, 1));
+    }
+
+    #[test]
+    fn narrow_counts() {
+        use Bag;
+
+        let mut bag = Bag::<char, u8>::default();
+        for c in "abracadabra".chars() {
+            bag.put(c);
+        }
+        assert_eq!(11u8, bag.len());
+        assert_eq!(5u8, bag.occurrence(&'a'));
+        assert_eq!(0u8, bag.occurrence(&'z'));
+    }
+
+    #[test]
+    fn put_overflow() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+        use Bag;
+
+        let mut bag = Bag::<char, u8>::default();
+        for _ in 0..255 {
+            bag.put('a');
+        }
+        assert!(catch_unwind(AssertUnwindSafe(|| bag.put('a'))).is_err());
+        assert_eq!(255u8, bag.occurrence(&'a'));
+    }
+
+    #[test]
+    fn merge_into_smaller() {
+        let mut small = bagof!('a');
+        small.merge(bagof!('a', 'b', 'c', 'c'));
+        assert_eq!(bagof!('a', 'a', 'b', 'c', 'c'), small);
+    }
 }
@@ -0,0 +1,278 @@
+//! N-gram language models estimated from counted token sequences.
+//!
+//! An `NGramModel` keeps a bag of n-grams for every order up to its own
+//! and answers `p(word | context)` queries, either as interpolated
+//! Kneser–Ney probabilities or as stupid-backoff scores.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::slice;
+
+use Bag;
+
+/// The backoff factor of stupid backoff, from Brants et al. (2007).
+const BACKOFF: f64 = 0.4;
+
+/// An n-gram language model over tokens of type `W`.
+///
+/// # Examples
+///
+/// ```
+/// use bag::lm::NGramModel;
+/// let model = NGramModel::from_tokens(2, "the cat sat on the mat".split(' '));
+/// assert_eq!(2, model.order());
+/// assert!(model.probability(&["the"], &"cat") > model.probability(&["the"], &"sat"));
+/// assert_eq!(0.5, model.stupid_backoff(&["the"], &"cat"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct NGramModel<W: Eq + Hash> {
+    levels: Vec<Level<W>>,
+    discount: f64,
+}
+
+/// The n-grams of one order, `levels[n - 1]` holding the n-grams.
+#[derive(Debug, Clone)]
+struct Level<W: Eq + Hash> {
+    counts: Bag<Vec<W>>,
+    /// The number of distinct tokens seen right before each n-gram.
+    continuations: Bag<Vec<W>>,
+    /// Totals of `counts` grouped by the leading `n - 1` tokens.
+    contexts: HashMap<Vec<W>, Tally>,
+    /// Totals of `continuations` grouped by the leading `n - 1` tokens.
+    continuation_contexts: HashMap<Vec<W>, Tally>,
+}
+
+/// The summed counts and the number of distinct tokens following a context.
+#[derive(Debug, Clone, Copy, Default)]
+struct Tally {
+    total: usize,
+    types: usize,
+}
+
+impl<W: Eq + Hash + Clone> NGramModel<W> {
+    /// Counts the n-grams of `tokens` of every order from one to `order`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is zero.
+    pub fn from_tokens<I: IntoIterator<Item = W>>(order: usize, tokens: I) -> Self {
+        assert!(order > 0, "order must be positive");
+        let tokens: Vec<W> = tokens.into_iter().collect();
+        let mut levels: Vec<Level<W>> = (1..=order)
+            .map(|n| {
+                let mut counts = Bag::new();
+                for gram in tokens.windows(n) {
+                    counts.put(gram.to_vec());
+                }
+                Level {
+                    counts,
+                    continuations: Bag::new(),
+                    contexts: HashMap::new(),
+                    continuation_contexts: HashMap::new(),
+                }
+            })
+            .collect();
+
+        for n in 1..order {
+            let continuations = {
+                let mut bag = Bag::new();
+                for gram in levels[n].counts.0.keys() {
+                    bag.put(gram[1..].to_vec());
+                }
+                bag
+            };
+            levels[n - 1].continuations = continuations;
+        }
+        for level in &mut levels {
+            level.contexts = tally(&level.counts);
+            level.continuation_contexts = tally(&level.continuations);
+        }
+        NGramModel {
+            levels,
+            discount: 0.75,
+        }
+    }
+}
+
+impl<W: Eq + Hash> NGramModel<W> {
+    /// Sets the absolute discount used by `probability`, `0.75` by default.
+    ///
+    /// # Panics
+    ///
+    /// Panics unless `discount` is in `(0, 1]`.
+    pub fn with_discount(mut self, discount: f64) -> Self {
+        assert!(
+            discount > 0.0 && discount <= 1.0,
+            "discount must be in (0, 1]"
+        );
+        self.discount = discount;
+        self
+    }
+
+    /// Returns the highest n-gram order of the model.
+    pub fn order(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Returns the number of times `gram` was seen; the empty gram counts
+    /// every token.
+    pub fn count(&self, gram: &[W]) -> usize {
+        match gram.len() {
+            0 => self.levels[0].counts.len(),
+            n if n <= self.levels.len() => gram_count(&self.levels[n - 1].counts, gram),
+            _ => 0,
+        }
+    }
+
+    /// Returns the number of distinct tokens seen.
+    pub fn vocabulary_len(&self) -> usize {
+        self.levels[0].counts.distinct_len()
+    }
+}
+
+impl<W: Eq + Hash + Clone> NGramModel<W> {
+    /// Returns the interpolated Kneser–Ney probability of `word` following
+    /// `context`, of which only the last `order - 1` tokens are used.
+    ///
+    /// Lower orders are interpolated down to a uniform distribution over
+    /// the vocabulary, so every known token has a nonzero probability and
+    /// the probabilities of the known tokens sum to one. An unknown token
+    /// has probability zero.
+    pub fn probability(&self, context: &[W], word: &W) -> f64 {
+        let context = &context[context.len().saturating_sub(self.levels.len() - 1)..];
+        self.kneser_ney(context, word, true)
+    }
+
+    fn kneser_ney(&self, context: &[W], word: &W, highest: bool) -> f64 {
+        let lower = match context.split_first() {
+            Some((_, rest)) => self.kneser_ney(rest, word, false),
+            None if gram_count(&self.levels[0].counts, slice::from_ref(word)) == 0 => {
+                return 0.0;
+            }
+            None => 1.0 / self.vocabulary_len() as f64,
+        };
+        let level = &self.levels[context.len()];
+        let (grams, contexts) = if highest {
+            (&level.counts, &level.contexts)
+        } else {
+            (&level.continuations, &level.continuation_contexts)
+        };
+        let tally = match contexts.get(context) {
+            Some(tally) => *tally,
+            None => return lower,
+        };
+        let count = gram_count(grams, &extend(context, word));
+        let total = tally.total as f64;
+        (count as f64 - self.discount).max(0.0) / total
+            + self.discount * tally.types as f64 / total * lower
+    }
+
+    /// Returns the perplexity of the model on `tokens`: the exponentiated
+    /// average negative log-probability of each token given the ones before
+    /// it.
+    ///
+    /// Lower is better. Any unknown token makes the perplexity infinite;
+    /// an empty sequence has perplexity one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::lm::NGramModel;
+    /// let model = NGramModel::from_tokens(2, "a b a b a b a c".split(' '));
+    /// let seen = model.perplexity("a b a b".split(' '));
+    /// let odd = model.perplexity("b b c c".split(' '));
+    /// assert!(seen < odd);
+    /// assert_eq!(f64::INFINITY, model.perplexity("a z".split(' ')));
+    /// ```
+    pub fn perplexity<I: IntoIterator<Item = W>>(&self, tokens: I) -> f64 {
+        let tokens: Vec<W> = tokens.into_iter().collect();
+        let history = self.levels.len() - 1;
+        let log_prob: f64 = tokens
+            .iter()
+            .enumerate()
+            .map(|(i, word)| {
+                self.probability(&tokens[i.saturating_sub(history)..i], word)
+                    .ln()
+            })
+            .sum();
+        match tokens.len() {
+            0 => 1.0,
+            n => (-log_prob / n as f64).exp(),
+        }
+    }
+
+    /// Returns the stupid-backoff score of `word` following `context`, of
+    /// which only the last `order - 1` tokens are used.
+    ///
+    /// The score is the relative frequency of the longest matching n-gram,
+    /// scaled by `0.4` for every order backed off. It is cheap, but it is
+    /// not a probability: scores over the vocabulary need not sum to one.
+    pub fn stupid_backoff(&self, context: &[W], word: &W) -> f64 {
+        let context = &context[context.len().saturating_sub(self.levels.len() - 1)..];
+        let mut scale = 1.0;
+        for start in 0..=context.len() {
+            let context = &context[start..];
+            let count = gram_count(&self.levels[context.len()].counts, &extend(context, word));
+            if count > 0 {
+                return scale * count as f64 / self.count(context) as f64;
+            }
+            scale *= BACKOFF;
+        }
+        0.0
+    }
+}
+
+fn gram_count<W: Eq + Hash>(grams: &Bag<Vec<W>>, gram: &[W]) -> usize {
+    grams.0.get(gram).map_or(0, |&c| c)
+}
+
+/// Returns `context` followed by `word`.
+fn extend<W: Clone>(context: &[W], word: &W) -> Vec<W> {
+    let mut gram = Vec::with_capacity(context.len() + 1);
+    gram.extend_from_slice(context);
+    gram.push(word.clone());
+    gram
+}
+
+/// Groups the counts of `grams` by all but their last token.
+fn tally<W: Eq + Hash + Clone>(grams: &Bag<Vec<W>>) -> HashMap<Vec<W>, Tally> {
+    let mut contexts: HashMap<Vec<W>, Tally> = HashMap::new();
+    for (gram, &count) in &grams.0 {
+        let tally = contexts.entry(gram[..gram.len() - 1].to_vec()).or_default();
+        tally.total += count;
+        tally.types += 1;
+    }
+    contexts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NGramModel;
+
+    #[test]
+    fn kneser_ney_sums_to_one() {
+        let text = "a b a c a b b a c c a b";
+        let model = NGramModel::from_tokens(3, text.split(' '));
+        for context in &[&["a", "b"][..], &["c", "c"], &["b"], &[], &["z", "a"]] {
+            let total: f64 = ["a", "b", "c"]
+                .iter()
+                .map(|w| model.probability(context, w))
+                .sum();
+            assert!(
+                (total - 1.0).abs() < 1e-9,
+                "{:?} sums to {}",
+                context,
+                total
+            );
+        }
+        assert_eq!(0.0, model.probability(&["a"], &"z"));
+    }
+
+    #[test]
+    fn stupid_backoff_backs_off() {
+        let model = NGramModel::from_tokens(2, "a b a c".split(' '));
+        assert_eq!(0.5, model.stupid_backoff(&["a"], &"b"));
+        assert_eq!(0.4 * 0.25, model.stupid_backoff(&["b"], &"c"));
+        assert_eq!(0.0, model.stupid_backoff(&["a"], &"z"));
+    }
+}
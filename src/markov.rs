@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use distribution::Distribution;
+use random::RandomSource;
+use Bag;
+
+/// A first-order Markov chain whose transitions are counted in bags.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use(bigram)]
+/// extern crate bag;
+/// use bag::MarkovModel;
+/// fn main() {
+///     let model = MarkovModel::from_bigrams(&bigram!("the", "cat", "sat", "on", "the", "mat"));
+///     assert_eq!(0.5, model.transition_probability(&"the", &"cat"));
+///     assert_eq!(1.0, model.transition_probability(&"cat", &"sat"));
+///     assert_eq!(0.0, model.transition_probability(&"mat", &"the"));
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkovModel<S: Eq + Hash> {
+    transitions: HashMap<S, Bag<S>>,
+}
+
+impl<S: Eq + Hash + Clone> MarkovModel<S> {
+    /// Builds the chain from counted `(from, to)` transitions.
+    pub fn from_bigrams(bigrams: &Bag<(S, S)>) -> Self {
+        let mut transitions: HashMap<S, Bag<S>> = HashMap::new();
+        for ((from, to), &count) in &bigrams.0 {
+            transitions
+                .entry(from.clone())
+                .or_default()
+                .0
+                .insert(to.clone(), count);
+        }
+        MarkovModel { transitions }
+    }
+
+    /// Returns the distribution of the states following `state`, or `None`
+    /// if `state` was never left.
+    pub fn next_distribution(&self, state: &S) -> Option<Distribution<S>> {
+        self.transitions.get(state).map(Bag::normalize)
+    }
+
+    /// Walks the chain from `start`, drawing each successor in proportion
+    /// to its count, and returns the visited states including `start`.
+    ///
+    /// The walk ends after `max_len` states or at a state that was never
+    /// left.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bigram)]
+    /// extern crate bag;
+    /// use bag::{MarkovModel, SplitMix64};
+    /// fn main() {
+    ///     let model = MarkovModel::from_bigrams(&bigram!("the", "cat", "sat", "on", "the", "mat"));
+    ///     let text = model.generate("cat", 10, &mut SplitMix64::new(7));
+    ///     assert_eq!(&["cat", "sat", "on", "the"], &text[..4]);
+    ///     assert!(text.len() == 10 || text.last() == Some(&"mat"));
+    /// }
+    /// ```
+    pub fn generate<R: RandomSource>(&self, start: S, max_len: usize, rng: &mut R) -> Vec<S> {
+        let mut walk = Vec::new();
+        if max_len == 0 {
+            return walk;
+        }
+        walk.push(start);
+        while walk.len() < max_len {
+            match self
+                .transitions
+                .get(&walk[walk.len() - 1])
+                .and_then(|next| next.sample(rng))
+            {
+                Some(state) => walk.push(state.clone()),
+                None => break,
+            }
+        }
+        walk
+    }
+}
+
+impl<S: Eq + Hash> MarkovModel<S> {
+    /// Returns the counted successors of `state`.
+    pub fn successors(&self, state: &S) -> Option<&Bag<S>> {
+        self.transitions.get(state)
+    }
+
+    /// Returns the probability of moving from `from` to `to`, zero if the
+    /// transition was never seen.
+    pub fn transition_probability(&self, from: &S, to: &S) -> f64 {
+        self.transitions
+            .get(from)
+            .map_or(0.0, |next| next.occurrence(to) as f64 / next.len() as f64)
+    }
+
+    /// Returns the number of states with at least one outgoing
+    /// transition.
+    pub fn len(&self) -> usize {
+        self.transitions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.transitions.is_empty()
+    }
+}
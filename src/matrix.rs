@@ -0,0 +1,91 @@
+use std::hash::Hash;
+
+use corpus::Corpus;
+use vocab::{Vocabulary, Weighting};
+
+/// A sparse matrix in compressed sparse row (CSR) format, produced by
+/// `Corpus::to_matrix`.
+///
+/// The entries of row `i` are `indices[indptr[i]..indptr[i + 1]]` with the
+/// matching `values`, sorted by column. This is the layout scipy and most
+/// Rust linear algebra crates accept directly.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SparseMatrix {
+    pub rows: usize,
+    pub cols: usize,
+    pub indptr: Vec<usize>,
+    pub indices: Vec<u32>,
+    pub values: Vec<f64>,
+}
+
+impl SparseMatrix {
+    /// Returns the number of stored entries.
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns the value at `row`, `col`, zero when it is not stored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `row` is out of bounds.
+    pub fn get(&self, row: usize, col: u32) -> f64 {
+        let range = self.indptr[row]..self.indptr[row + 1];
+        match self.indices[range.clone()].binary_search(&col) {
+            Ok(i) => self.values[range.start + i],
+            Err(_) => 0.0,
+        }
+    }
+}
+
+impl<E: Eq + Hash + Clone> Corpus<E> {
+    /// Builds the document-term matrix over `vocab`, one row per document
+    /// in name order, and returns it with the row names.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// use bag::{Corpus, Vocabulary, Weighting};
+    /// fn main() {
+    ///     let mut corpus = Corpus::new();
+    ///     corpus.insert("b", bagof!("dog", "dog"));
+    ///     corpus.insert("a", bagof!("cat", "dog"));
+    ///     let mut vocab = Vocabulary::new();
+    ///     vocab.insert("cat");
+    ///     vocab.insert("dog");
+    ///
+    ///     let (names, matrix) = corpus.to_matrix(&vocab, Weighting::Count);
+    ///     assert_eq!(vec!["a", "b"], names);
+    ///     assert_eq!(vec![0, 2, 3], matrix.indptr);
+    ///     assert_eq!(2.0, matrix.get(1, 1));
+    ///     assert_eq!(0.0, matrix.get(1, 0));
+    /// }
+    /// ```
+    pub fn to_matrix(
+        &self,
+        vocab: &Vocabulary<E>,
+        weighting: Weighting<E>,
+    ) -> (Vec<&str>, SparseMatrix) {
+        let mut docs: Vec<_> = self.documents().collect();
+        docs.sort_by_key(|&(name, _)| name);
+
+        let mut matrix = SparseMatrix {
+            rows: docs.len(),
+            cols: vocab.len(),
+            indptr: Vec::with_capacity(docs.len() + 1),
+            indices: Vec::new(),
+            values: Vec::new(),
+        };
+        matrix.indptr.push(0);
+        for &(_, doc) in &docs {
+            for (id, value) in doc.to_sparse_vec(vocab, weighting) {
+                matrix.indices.push(id);
+                matrix.values.push(value);
+            }
+            matrix.indptr.push(matrix.indices.len());
+        }
+        (docs.into_iter().map(|(name, _)| name).collect(), matrix)
+    }
+}
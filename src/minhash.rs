@@ -0,0 +1,95 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use random::{RandomSource, SplitMix64};
+use {Bag, Count};
+
+/// A MinHash signature of the set of distinct elements of a bag, created by
+/// `Bag::minhash`.
+///
+/// Comparing two signatures estimates the Jaccard similarity of the bags
+/// they came from without access to the bags themselves. The standard error
+/// of the estimate shrinks with the square root of the signature length.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MinHashSignature(Vec<u64>);
+
+impl MinHashSignature {
+    /// Returns the number of hash functions in the signature.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the minimum hash values, one per hash function.
+    pub fn values(&self) -> &[u64] {
+        &self.0
+    }
+
+    /// Estimates the Jaccard similarity of the sets the two signatures were
+    /// computed from, as the fraction of agreeing minimum values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the signatures have different lengths.
+    pub fn estimate_jaccard(&self, other: &MinHashSignature) -> f64 {
+        assert_eq!(
+            self.0.len(),
+            other.0.len(),
+            "signatures must have the same length"
+        );
+        if self.0.is_empty() {
+            return 1.0;
+        }
+        let agree = self.0.iter().zip(&other.0).filter(|(a, b)| a == b).count();
+        agree as f64 / self.0.len() as f64
+    }
+}
+
+impl<T: Eq + Hash, C: Count> Bag<T, C> {
+    /// Computes a MinHash signature of the distinct elements using
+    /// `num_hashes` hash functions.
+    ///
+    /// Signatures are deterministic, so they can be stored and compared
+    /// with signatures computed elsewhere with the same `num_hashes`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::Bag;
+    /// let a: Bag<u32> = (0..1000).collect();
+    /// let b: Bag<u32> = (500..1500).collect();
+    /// let estimate = a.minhash(256).estimate_jaccard(&b.minhash(256));
+    /// assert!((estimate - a.jaccard(&b)).abs() < 0.1);
+    /// ```
+    pub fn minhash(&self, num_hashes: usize) -> MinHashSignature {
+        let mut mins = vec![u64::MAX; num_hashes];
+        for elem in self.0.keys() {
+            let mut hasher = DefaultHasher::new();
+            elem.hash(&mut hasher);
+            // Successive outputs of a generator seeded by the element hash
+            // act as its hash under each of the functions.
+            let mut hashes = SplitMix64::new(hasher.finish());
+            for min in &mut mins {
+                *min = (*min).min(hashes.next_u64());
+            }
+        }
+        MinHashSignature(mins)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use Bag;
+
+    #[test]
+    fn identical_and_disjoint() {
+        let a: Bag<char> = "abcdef".chars().collect();
+        let b: Bag<char> = "uvwxyz".chars().collect();
+        assert_eq!(1.0, a.minhash(64).estimate_jaccard(&a.minhash(64)));
+        assert!(a.minhash(64).estimate_jaccard(&b.minhash(64)) < 0.2);
+        assert_eq!(64, a.minhash(64).len());
+    }
+}
@@ -0,0 +1,295 @@
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use std::iter::Fuse;
+
+use Bag;
+
+/// An iterator over the overlapping `N`-grams of another iterator, created
+/// by `ngrams`.
+///
+/// Only the last `N` items are buffered, so the input is never collected.
+#[derive(Debug, Clone)]
+pub struct NGrams<I: Iterator, const N: usize> {
+    iter: Fuse<I>,
+    window: VecDeque<I::Item>,
+}
+
+/// Adapts `iter` into its overlapping `N`-grams. Yields nothing when `N` is
+/// zero or the input is shorter than `N`.
+///
+/// # Examples
+///
+/// ```
+/// use bag::ngram::ngrams;
+/// let grams: Vec<[char; 2]> = ngrams("abc".chars()).collect();
+/// assert_eq!(vec![['a', 'b'], ['b', 'c']], grams);
+/// ```
+pub fn ngrams<I, const N: usize>(iter: I) -> NGrams<I::IntoIter, N>
+where
+    I: IntoIterator,
+    I::Item: Clone,
+{
+    NGrams {
+        iter: iter.into_iter().fuse(),
+        window: VecDeque::with_capacity(N),
+    }
+}
+
+impl<I: Iterator, const N: usize> Iterator for NGrams<I, N>
+where
+    I::Item: Clone,
+{
+    type Item = [I::Item; N];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if N == 0 {
+            return None;
+        }
+        if self.window.len() == N {
+            self.window.pop_front();
+        }
+        while self.window.len() < N {
+            self.window.push_back(self.iter.next()?);
+        }
+        let window = &self.window;
+        Some(::std::array::from_fn(|i| window[i].clone()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if N == 0 {
+            return (0, Some(0));
+        }
+        // A full window has already been yielded and only its tail carries
+        // over into the next one.
+        let buffered = self.window.len().min(N - 1);
+        let (lower, upper) = self.iter.size_hint();
+        let windows = |rest: usize| rest.saturating_add(buffered).saturating_sub(N - 1);
+        (
+            windows(lower),
+            upper
+                .and_then(|u| u.checked_add(buffered))
+                .map(|u| u.saturating_sub(N - 1)),
+        )
+    }
+}
+
+impl<E: Eq + Hash + Clone, const N: usize> Bag<[E; N]> {
+    /// Counts the overlapping `N`-grams of `iter`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::Bag;
+    /// let words = "a rose is a rose is a rose".split(' ');
+    /// let trigrams: Bag<[&str; 3]> = Bag::from_ngrams(words);
+    /// assert_eq!(2, trigrams.occurrence(&["a", "rose", "is"]));
+    /// assert_eq!(6, trigrams.len());
+    /// ```
+    pub fn from_ngrams<I: IntoIterator<Item = E>>(iter: I) -> Self {
+        let mut bag = Bag::new();
+        for gram in ngrams(iter) {
+            bag.put(gram);
+        }
+        bag
+    }
+}
+
+impl<E: Eq + Hash + Clone> Bag<Vec<E>> {
+    /// Counts the overlapping windows of `n` elements of `iter`, for when
+    /// `n` is only known at run time; see `from_ngrams` otherwise.
+    ///
+    /// Only the last `n` elements are buffered. Nothing is counted when `n`
+    /// is zero or the input is shorter than `n`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::Bag;
+    /// let windows = Bag::count_windows("abcab".chars(), 2);
+    /// assert_eq!(2, windows.occurrence(&vec!['a', 'b']));
+    /// assert_eq!(4, windows.len());
+    /// ```
+    pub fn count_windows<I: IntoIterator<Item = E>>(iter: I, n: usize) -> Self {
+        let mut bag = Bag::new();
+        if n == 0 {
+            return bag;
+        }
+        let mut window: VecDeque<E> = VecDeque::with_capacity(n);
+        for elem in iter {
+            if window.len() == n {
+                window.pop_front();
+            }
+            window.push_back(elem);
+            if window.len() == n {
+                bag.put(window.iter().cloned().collect());
+            }
+        }
+        bag
+    }
+}
+
+impl<E: Eq + Hash + Clone> Bag<(E, E)> {
+    /// Counts the ordered skip-gram pairs of `iter`: every `(a, b)` where `b`
+    /// follows `a` inside a sliding window of `window` elements with at most
+    /// `max_skip` elements between them.
+    ///
+    /// With `max_skip` zero this counts plain bigrams. Nothing is counted
+    /// when `window` is below two.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::Bag;
+    /// let pairs = Bag::from_skipgrams("abcd".chars(), 3, 1);
+    /// assert_eq!(1, pairs.occurrence(&('a', 'b')));
+    /// assert_eq!(1, pairs.occurrence(&('a', 'c')));
+    /// assert_eq!(0, pairs.occurrence(&('a', 'd')));
+    /// assert_eq!(5, pairs.len());
+    /// ```
+    pub fn from_skipgrams<I: IntoIterator<Item = E>>(
+        iter: I,
+        window: usize,
+        max_skip: usize,
+    ) -> Self {
+        let mut bag = Bag::new();
+        let span = window.saturating_sub(1).min(max_skip.saturating_add(1));
+        if span == 0 {
+            return bag;
+        }
+        let mut recent: VecDeque<E> = VecDeque::with_capacity(span);
+        for elem in iter {
+            for prev in &recent {
+                bag.put((prev.clone(), elem.clone()));
+            }
+            if recent.len() == span {
+                recent.pop_front();
+            }
+            recent.push_back(elem);
+        }
+        bag
+    }
+}
+
+impl<E: Eq + Hash + Ord + Clone> Bag<(E, E)> {
+    /// Counts the unordered pairs of elements of `iter` at most `window`
+    /// positions apart, storing each pair smaller element first.
+    ///
+    /// An element paired with an equal one nearby counts as `(e, e)`.
+    /// Nothing is counted when `window` is zero. See
+    /// `weighted_cooccurrences` to weight pairs by their distance.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::Bag;
+    /// let pairs = Bag::from_cooccurrences("abca".chars(), 2);
+    /// assert_eq!(2, pairs.occurrence(&('a', 'b')));
+    /// assert_eq!(2, pairs.occurrence(&('a', 'c')));
+    /// assert_eq!(0, pairs.occurrence(&('b', 'a')));
+    /// assert_eq!(5, pairs.len());
+    /// ```
+    pub fn from_cooccurrences<I: IntoIterator<Item = E>>(iter: I, window: usize) -> Self {
+        let mut bag = Bag::new();
+        for_each_cooccurrence(iter, window, |pair, _| bag.put(pair));
+        bag
+    }
+}
+
+/// Sums the unordered pairs of elements of `iter` at most `window`
+/// positions apart, weighting each pair by `1 / distance` as in GloVe
+/// co-occurrence matrices.
+///
+/// # Examples
+///
+/// ```
+/// use bag::ngram::weighted_cooccurrences;
+/// let weights = weighted_cooccurrences("abc".chars(), 2);
+/// assert_eq!(Some(&1.0), weights.get(&('a', 'b')));
+/// assert_eq!(Some(&0.5), weights.get(&('a', 'c')));
+/// ```
+pub fn weighted_cooccurrences<E, I>(iter: I, window: usize) -> HashMap<(E, E), f64>
+where
+    E: Eq + Hash + Ord + Clone,
+    I: IntoIterator<Item = E>,
+{
+    let mut weights = HashMap::new();
+    for_each_cooccurrence(iter, window, |pair, distance| {
+        *weights.entry(pair).or_insert(0.0) += 1.0 / distance as f64;
+    });
+    weights
+}
+
+/// Calls `emit` with every ordered-by-value pair of elements at most
+/// `window` positions apart and their distance.
+fn for_each_cooccurrence<E, I, F>(iter: I, window: usize, mut emit: F)
+where
+    E: Ord + Clone,
+    I: IntoIterator<Item = E>,
+    F: FnMut((E, E), usize),
+{
+    if window == 0 {
+        return;
+    }
+    let mut recent: VecDeque<E> = VecDeque::with_capacity(window);
+    for elem in iter {
+        for (i, prev) in recent.iter().enumerate() {
+            let pair = if *prev <= elem {
+                (prev.clone(), elem.clone())
+            } else {
+                (elem.clone(), prev.clone())
+            };
+            emit(pair, recent.len() - i);
+        }
+        if recent.len() == window {
+            recent.pop_front();
+        }
+        recent.push_back(elem);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ngrams;
+    use Bag;
+
+    /// Yields `0`, then `None`, then `1, 2`, then `None` for good.
+    struct Gappy(u8);
+
+    impl Iterator for Gappy {
+        type Item = u8;
+
+        fn next(&mut self) -> Option<u8> {
+            self.0 += 1;
+            match self.0 {
+                1 => Some(0),
+                3 => Some(1),
+                4 => Some(2),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn short_inputs() {
+        assert_eq!(0, ngrams::<_, 3>(vec![1, 2]).count());
+        assert_eq!(0, ngrams::<_, 0>(vec![1, 2]).count());
+        assert_eq!(vec![[1]], ngrams::<_, 1>(vec![1]).collect::<Vec<_>>());
+        assert!(Bag::<[u8; 2]>::from_ngrams(vec![]).is_empty());
+        let mut gappy = ngrams::<_, 2>(Gappy(0));
+        assert_eq!(None, gappy.next());
+        assert_eq!(None, gappy.next());
+        assert!(Bag::count_windows(vec![1, 2], 3).is_empty());
+        assert!(Bag::count_windows(vec![1, 2], 0).is_empty());
+    }
+
+    #[test]
+    fn size_hint() {
+        let mut grams = ngrams::<_, 3>(0..5);
+        assert_eq!((3, Some(3)), grams.size_hint());
+        grams.next();
+        assert_eq!((2, Some(2)), grams.size_hint());
+        assert_eq!(2, grams.by_ref().count());
+        assert_eq!((0, Some(0)), grams.size_hint());
+        assert_eq!((0, Some(0)), ngrams::<_, 3>(0..2).size_hint());
+    }
+}
@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use iter::{IndexDistinct, IndexFrequency};
+use Bag;
+
+/// A bag that remembers the order in which elements first appeared.
+///
+/// `frequency` and `distinct` iterate in first-insertion order, and each
+/// distinct element can be looked up by its position with `get_index`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexBag<T: Eq + Hash + Clone> {
+    entries: Vec<(T, usize)>,
+    index: HashMap<T, usize>,
+}
+
+impl<T: Eq + Hash + Clone> Default for IndexBag<T> {
+    fn default() -> Self {
+        IndexBag::new()
+    }
+}
+
+impl<T: Eq + Hash + Clone> IndexBag<T> {
+    /// Creates a new empty `IndexBag`.
+    pub fn new() -> Self {
+        IndexBag {
+            entries: Vec::new(),
+            index: HashMap::new(),
+        }
+    }
+
+    /// Insert an element.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::IndexBag;
+    /// let mut bag = IndexBag::new();
+    /// for word in "b a b c a b".split(' ') {
+    ///     bag.put(word);
+    /// }
+    /// assert_eq!(vec![&"b", &"a", &"c"], bag.distinct().collect::<Vec<_>>());
+    /// assert_eq!(Some((&"a", 2)), bag.get_index(1));
+    /// ```
+    pub fn put(&mut self, elem: T) {
+        if let Some(&i) = self.index.get(&elem) {
+            self.entries[i].1 += 1;
+            return;
+        }
+        self.index.insert(elem.clone(), self.entries.len());
+        self.entries.push((elem, 1));
+    }
+
+    /// Counts the occurrences of `elem`.
+    pub fn occurrence(&self, elem: &T) -> usize {
+        self.index.get(elem).map_or(0, |&i| self.entries[i].1)
+    }
+
+    /// Returns the `i`-th distinct element in insertion order with its
+    /// count.
+    pub fn get_index(&self, i: usize) -> Option<(&T, usize)> {
+        self.entries.get(i).map(|(elem, count)| (elem, *count))
+    }
+
+    /// Returns the position of `elem` in insertion order.
+    pub fn index_of(&self, elem: &T) -> Option<usize> {
+        self.index.get(elem).cloned()
+    }
+
+    /// Counts all the elements, including each duplicate.
+    pub fn len(&self) -> usize {
+        self.entries.iter().map(|&(_, count)| count).sum()
+    }
+
+    pub fn distinct_len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterates over the elements and their counts in insertion order.
+    pub fn frequency(&self) -> IndexFrequency<'_, T> {
+        IndexFrequency(self.entries.iter())
+    }
+
+    /// Iterates over the distinct elements in insertion order.
+    pub fn distinct(&self) -> IndexDistinct<'_, T> {
+        IndexDistinct(self.entries.iter())
+    }
+
+    /// Consumes the wrapper, returning an unordered bag with the same
+    /// counts.
+    pub fn into_bag(self) -> Bag<T> {
+        Bag(self.entries.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IndexBag;
+
+    #[test]
+    fn order_is_first_insertion() {
+        let mut bag = IndexBag::new();
+        for c in "abracadabra".chars() {
+            bag.put(c);
+        }
+        let order: String = bag.distinct().collect();
+        assert_eq!("abrcd", order);
+        assert_eq!(Some(2), bag.index_of(&'r'));
+        assert_eq!(None, bag.get_index(5));
+        assert_eq!(11, bag.len());
+        assert_eq!(5, bag.distinct().len());
+        assert_eq!(Some((&'a', &5)), bag.frequency().next());
+        assert_eq!(
+            bagof!('a', 'b', 'r', 'a', 'c', 'a', 'd', 'a', 'b', 'r', 'a'),
+            bag.into_bag()
+        );
+    }
+}
@@ -0,0 +1,167 @@
+use std::hash::Hash;
+
+use random::RandomSource;
+use {Bag, Count};
+
+impl<T: Eq + Hash + Clone, C: Count> Bag<T, C> {
+    /// Returns a copy of the bag with two-sided geometric noise (the discrete
+    /// Laplace mechanism) added to every count, giving `epsilon`-differential
+    /// privacy for counts with the given L1 `sensitivity`.
+    ///
+    /// Noised counts that drop to zero or below are removed. Only the counts
+    /// are protected, not the set of keys; use `export_dp_thresholded` to
+    /// suppress the small counts that would reveal rare keys.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `epsilon` or `sensitivity` is not positive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::{Bag, SplitMix64};
+    /// let mut bag = Bag::new();
+    /// for _ in 0..1000 {
+    ///     bag.put("common");
+    /// }
+    /// let noised = bag.export_dp(1.0, 1.0, &mut SplitMix64::new(1));
+    /// let count = noised.occurrence(&"common") as f64;
+    /// assert!((count - 1000.0).abs() < 50.0);
+    /// ```
+    pub fn export_dp<R: RandomSource>(&self, epsilon: f64, sensitivity: f64, rng: &mut R) -> Self {
+        self.export_dp_thresholded(epsilon, sensitivity, C::ONE, rng)
+    }
+
+    /// Like `export_dp`, but also drops every noised count below
+    /// `threshold`.
+    pub fn export_dp_thresholded<R: RandomSource>(
+        &self,
+        epsilon: f64,
+        sensitivity: f64,
+        threshold: C,
+        rng: &mut R,
+    ) -> Self {
+        assert!(epsilon > 0.0, "epsilon must be positive");
+        assert!(sensitivity > 0.0, "sensitivity must be positive");
+        let ln_alpha = -epsilon / sensitivity;
+
+        let mut noised = Bag::default();
+        for (elem, &count) in &self.0 {
+            let noise = geometric(rng, ln_alpha) - geometric(rng, ln_alpha);
+            let value = count.to_f64() + noise;
+            if value >= 1.0 {
+                let value = C::from_f64(value);
+                if value >= threshold {
+                    noised.0.insert(elem.clone(), value);
+                }
+            }
+        }
+        noised
+    }
+}
+
+/// How much was removed by `Bag::suppress_below`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Suppression<C> {
+    /// Number of distinct elements removed.
+    pub distinct: usize,
+    /// Total occurrences of the removed elements.
+    pub mass: C,
+}
+
+impl<T: Eq + Hash, C: Count> Bag<T, C> {
+    /// Removes every element occurring fewer than `k` times, the usual
+    /// k-anonymity rule applied before publishing a frequency table, and
+    /// reports what was removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let mut bag = bagof!("a", "a", "a", "b", "c", "c");
+    ///     let suppressed = bag.suppress_below(3);
+    ///     assert_eq!(2, suppressed.distinct);
+    ///     assert_eq!(3, suppressed.mass);
+    ///     assert_eq!(bagof!("a", "a", "a"), bag);
+    /// }
+    /// ```
+    pub fn suppress_below(&mut self, k: C) -> Suppression<C> {
+        let mut suppressed = Suppression {
+            distinct: 0,
+            mass: C::ZERO,
+        };
+        self.0.retain(|_, &mut count| {
+            if count < k {
+                suppressed.distinct += 1;
+                suppressed.mass += count;
+                false
+            } else {
+                true
+            }
+        });
+        suppressed
+    }
+
+    /// Like `suppress_below`, but folds the removed occurrences into the
+    /// catch-all element `other` instead of dropping them, so the total is
+    /// preserved.
+    ///
+    /// `other` itself is exempt from suppression.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let mut bag = bagof!("a", "a", "a", "b", "c", "c");
+    ///     bag.suppress_below_into(3, "other");
+    ///     assert_eq!(bagof!("a", "a", "a", "other", "other", "other"), bag);
+    /// }
+    /// ```
+    pub fn suppress_below_into(&mut self, k: C, other: T) -> Suppression<C> {
+        let kept = self.0.remove(&other);
+        let suppressed = self.suppress_below(k);
+        let total = kept.unwrap_or(C::ZERO) + suppressed.mass;
+        if total != C::ZERO {
+            self.0.insert(other, total);
+        }
+        suppressed
+    }
+}
+
+/// Samples the number of failures before the first success of a Bernoulli
+/// trial with failure probability `exp(ln_alpha)`.
+fn geometric<R: RandomSource>(rng: &mut R, ln_alpha: f64) -> f64 {
+    let u = 1.0 - rng.next_f64();
+    (u.ln() / ln_alpha).floor()
+}
+
+#[cfg(test)]
+mod tests {
+    use random::SplitMix64;
+    use Bag;
+
+    #[test]
+    fn noise_is_centered() {
+        let mut bag = Bag::new();
+        for i in 0..2000 {
+            for _ in 0..50 {
+                bag.put(i);
+            }
+        }
+        let noised = bag.export_dp(0.5, 1.0, &mut SplitMix64::new(42));
+        let mean = noised.len() as f64 / 2000.0;
+        assert!((mean - 50.0).abs() < 0.5, "mean {}", mean);
+        assert!(noised.frequency().any(|(_, &c)| c != 50));
+    }
+
+    #[test]
+    fn threshold_suppresses_small_counts() {
+        let bag = bagof!("rare", "common", "common", "common", "common");
+        let noised = bag.export_dp_thresholded(100.0, 1.0, 3, &mut SplitMix64::new(3));
+        assert_eq!(bagof!("common", "common", "common", "common"), noised);
+    }
+}
@@ -0,0 +1,200 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::hash::Hash;
+use std::ops::{Bound, RangeBounds};
+
+use {Bag, Count};
+
+type KeyFilter<'a, T> = Box<dyn Fn(&T) -> bool + 'a>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Order {
+    Ascending,
+    Descending,
+}
+
+/// A filter/sort/limit pipeline over a `Bag`, created by `Bag::query`.
+///
+/// Nothing is evaluated until `run`. Sorting combined with a limit keeps a
+/// bounded heap instead of sorting every matching element.
+pub struct Query<'a, T: Eq + Hash + 'a, C: Count + 'a> {
+    bag: &'a Bag<T, C>,
+    counts: Vec<(Bound<C>, Bound<C>)>,
+    keys: Vec<KeyFilter<'a, T>>,
+    order: Option<Order>,
+    limit: Option<usize>,
+}
+
+impl<'a, T: Eq + Hash, C: Count> Query<'a, T, C> {
+    /// Keeps the elements whose count lies in `range`.
+    pub fn filter_count<R: RangeBounds<C>>(mut self, range: R) -> Self {
+        self.counts
+            .push((range.start_bound().cloned(), range.end_bound().cloned()));
+        self
+    }
+
+    /// Keeps the elements for which `pred` returns `true`.
+    pub fn filter_key<F: Fn(&T) -> bool + 'a>(mut self, pred: F) -> Self {
+        self.keys.push(Box::new(pred));
+        self
+    }
+
+    /// Orders the result by ascending count.
+    pub fn sort_asc(mut self) -> Self {
+        self.order = Some(Order::Ascending);
+        self
+    }
+
+    /// Orders the result by descending count.
+    pub fn sort_desc(mut self) -> Self {
+        self.order = Some(Order::Descending);
+        self
+    }
+
+    /// Keeps at most `n` rows.
+    pub fn limit(mut self, n: usize) -> Self {
+        self.limit = Some(n);
+        self
+    }
+
+    /// Evaluates the pipeline, returning the matching elements and their
+    /// counts.
+    pub fn run(self) -> Vec<(&'a T, C)> {
+        let counts = self.counts;
+        let keys = self.keys;
+        let rows = self
+            .bag
+            .0
+            .iter()
+            .map(|(elem, &count)| (elem, count))
+            .filter(|&(elem, count)| {
+                counts.iter().all(|range| range.contains(&count))
+                    && keys.iter().all(|pred| pred(elem))
+            });
+
+        match (self.order, self.limit) {
+            (None, None) => rows.collect(),
+            (None, Some(n)) => rows.take(n).collect(),
+            (Some(order), None) => {
+                let mut rows: Vec<_> = rows.collect();
+                match order {
+                    Order::Ascending => rows.sort_by_key(|&(_, count)| count),
+                    Order::Descending => rows.sort_by_key(|&(_, count)| Reverse(count)),
+                }
+                rows
+            }
+            (Some(Order::Ascending), Some(n)) => {
+                // Max-heap of the n smallest seen so far.
+                let mut heap = BinaryHeap::with_capacity(n + 1);
+                for (elem, count) in rows {
+                    heap.push(ByCount(count, elem));
+                    if heap.len() > n {
+                        heap.pop();
+                    }
+                }
+                heap.into_sorted_vec()
+                    .into_iter()
+                    .map(|ByCount(c, e)| (e, c))
+                    .collect()
+            }
+            (Some(Order::Descending), Some(n)) => {
+                // Min-heap of the n largest seen so far.
+                let mut heap = BinaryHeap::with_capacity(n + 1);
+                for (elem, count) in rows {
+                    heap.push(Reverse(ByCount(count, elem)));
+                    if heap.len() > n {
+                        heap.pop();
+                    }
+                }
+                heap.into_sorted_vec()
+                    .into_iter()
+                    .map(|Reverse(ByCount(c, e))| (e, c))
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Orders rows by count alone, so elements need not be `Ord`.
+struct ByCount<'a, T: 'a, C>(C, &'a T);
+
+impl<'a, T, C: Ord> PartialEq for ByCount<'a, T, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<'a, T, C: Ord> Eq for ByCount<'a, T, C> {}
+
+impl<'a, T, C: Ord> PartialOrd for ByCount<'a, T, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'a, T, C: Ord> Ord for ByCount<'a, T, C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+impl<T: Eq + Hash, C: Count> Bag<T, C> {
+    /// Starts a query pipeline over the elements and their counts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let bag = bagof!("a", "a", "a", "b", "b", "c", "dd", "dd");
+    ///     let rows = bag
+    ///         .query()
+    ///         .filter_count(2..)
+    ///         .filter_key(|k| k.len() == 1)
+    ///         .sort_desc()
+    ///         .limit(20)
+    ///         .run();
+    ///     assert_eq!(vec![(&"a", 3), (&"b", 2)], rows);
+    /// }
+    /// ```
+    pub fn query(&self) -> Query<'_, T, C> {
+        Query {
+            bag: self,
+            counts: Vec::new(),
+            keys: Vec::new(),
+            order: None,
+            limit: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use Bag;
+
+    #[test]
+    fn heap_matches_full_sort() {
+        let mut bag = Bag::new();
+        for i in 0..200u32 {
+            for _ in 0..(i * 7 % 31) {
+                bag.put(i);
+            }
+        }
+        let counts =
+            |rows: Vec<(&u32, usize)>| rows.into_iter().map(|(_, c)| c).collect::<Vec<_>>();
+
+        let full = counts(bag.query().sort_desc().run());
+        assert_eq!(
+            full[..10].to_vec(),
+            counts(bag.query().sort_desc().limit(10).run())
+        );
+
+        let full = counts(bag.query().filter_count(..10).sort_asc().run());
+        assert!(full.iter().all(|&c| c < 10));
+        assert_eq!(
+            full[..5].to_vec(),
+            counts(bag.query().filter_count(..10).sort_asc().limit(5).run())
+        );
+    }
+}
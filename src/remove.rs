@@ -0,0 +1,109 @@
+use std::borrow::Borrow;
+use std::hash::Hash;
+
+use error::UnderflowError;
+use {Bag, Count};
+
+/// What `Bag::remove_n` does when asked to remove more occurrences than
+/// the bag holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RemovePolicy {
+    /// Remove every occurrence there is.
+    Saturate,
+    /// Leave the bag unchanged and return an `UnderflowError`.
+    Error,
+    /// Panic.
+    Panic,
+}
+
+impl<T: Eq + Hash, C: Count> Bag<T, C> {
+    /// Removes `n` occurrences of `elem`, returning how many were actually
+    /// removed. `policy` decides what happens when the bag holds fewer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bag holds fewer than `n` occurrences and `policy` is
+    /// `RemovePolicy::Panic`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// use bag::{RemovePolicy, UnderflowError};
+    /// fn main() {
+    ///     let mut bag = bagof!("a", "a", "a");
+    ///     assert_eq!(Ok(2), bag.remove_n("a", 2, RemovePolicy::Error));
+    ///     assert_eq!(Err(UnderflowError), bag.remove_n("a", 2, RemovePolicy::Error));
+    ///     assert_eq!(1, bag.occurrence(&"a"));
+    ///     assert_eq!(Ok(1), bag.remove_n("a", 2, RemovePolicy::Saturate));
+    ///     assert!(bag.is_empty());
+    /// }
+    /// ```
+    pub fn remove_n<Q>(&mut self, elem: &Q, n: C, policy: RemovePolicy) -> Result<C, UnderflowError>
+    where
+        T: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let count = self.0.get(elem).map_or(C::ZERO, |&count| count);
+        let removed = if n <= count {
+            n
+        } else {
+            match policy {
+                RemovePolicy::Saturate => count,
+                RemovePolicy::Error => return Err(UnderflowError),
+                RemovePolicy::Panic => panic!("removing more occurrences than the bag holds"),
+            }
+        };
+        if removed == count {
+            self.0.remove(elem);
+        } else if let Some(stored) = self.0.get_mut(elem) {
+            *stored -= removed;
+        }
+        Ok(removed)
+    }
+
+    /// Removes every occurrence of `elem`, returning the owned element and
+    /// its count, or `None` if it was absent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let mut bag = bagof!(String::from("a"), String::from("a"));
+    ///     assert_eq!(Some((String::from("a"), 2)), bag.take("a"));
+    ///     assert_eq!(None, bag.take("a"));
+    ///     assert!(bag.is_empty());
+    /// }
+    /// ```
+    pub fn take<Q>(&mut self, elem: &Q) -> Option<(T, C)>
+    where
+        T: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.0.remove_entry(elem)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RemovePolicy;
+
+    #[test]
+    fn remove_absent() {
+        let mut bag = bagof!('a');
+        assert_eq!(Ok(0), bag.remove_n(&'b', 0, RemovePolicy::Error));
+        assert_eq!(Ok(0), bag.remove_n(&'b', 3, RemovePolicy::Saturate));
+        assert!(bag.remove_n(&'b', 1, RemovePolicy::Error).is_err());
+        assert_eq!(bagof!('a'), bag);
+    }
+
+    #[test]
+    #[should_panic(expected = "more occurrences")]
+    fn remove_panics() {
+        let mut bag = bagof!('a');
+        let _ = bag.remove_n(&'a', 2, RemovePolicy::Panic);
+    }
+}
@@ -0,0 +1,305 @@
+use std::hash::Hash;
+
+use random::RandomSource;
+use {Bag, Count};
+
+/// Cumulative weights over a `Bag` for repeated weighted draws, created by
+/// `Bag::sampler`.
+#[derive(Debug, Clone)]
+pub struct Sampler<'a, T: 'a> {
+    elems: Vec<&'a T>,
+    cumulative: Vec<f64>,
+}
+
+impl<'a, T> Sampler<'a, T> {
+    /// Draws an element with probability proportional to its count in
+    /// `O(log n)`, or `None` if the bag was empty.
+    pub fn sample<R: RandomSource>(&self, rng: &mut R) -> Option<&'a T> {
+        let total = *self.cumulative.last()?;
+        let target = rng.next_f64() * total;
+        let i = self.cumulative.partition_point(|&c| c <= target);
+        Some(self.elems[i.min(self.elems.len() - 1)])
+    }
+}
+
+/// An alias table for word2vec-style negative sampling, created by
+/// `Bag::sampling_table`.
+#[derive(Debug, Clone)]
+pub struct SamplingTable<'a, T: 'a> {
+    elems: Vec<&'a T>,
+    // Column `i` keeps `elems[i]` with probability `keep[i]` and otherwise
+    // yields `elems[alias[i]]`.
+    keep: Vec<f64>,
+    alias: Vec<usize>,
+}
+
+impl<'a, T> SamplingTable<'a, T> {
+    /// Draws an element in `O(1)`, or `None` if the bag was empty.
+    pub fn sample<R: RandomSource>(&self, rng: &mut R) -> Option<&'a T> {
+        if self.elems.is_empty() {
+            return None;
+        }
+        let i = rng.below(self.elems.len() as u64) as usize;
+        if rng.next_f64() < self.keep[i] {
+            Some(self.elems[i])
+        } else {
+            Some(self.elems[self.alias[i]])
+        }
+    }
+}
+
+impl<T: Eq + Hash, C: Count> Bag<T, C> {
+    /// Draws an element with probability proportional to its count, or
+    /// `None` if the bag is empty.
+    ///
+    /// Each call scans the counts without allocating; build a `sampler` for
+    /// many draws from the same bag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// use bag::SplitMix64;
+    /// fn main() {
+    ///     let bag = bagof!("heads", "heads", "heads", "tails");
+    ///     let mut rng = SplitMix64::new(0);
+    ///     let heads = (0..1000)
+    ///         .filter(|_| bag.sample(&mut rng) == Some(&"heads"))
+    ///         .count();
+    ///     assert!(heads > 700 && heads < 800);
+    /// }
+    /// ```
+    pub fn sample<R: RandomSource>(&self, rng: &mut R) -> Option<&T> {
+        let mut target = rng.next_f64() * self.len().to_f64();
+        let mut last = None;
+        for (elem, &count) in &self.0 {
+            let weight = count.to_f64();
+            if target < weight {
+                return Some(elem);
+            }
+            target -= weight;
+            last = Some(elem);
+        }
+        // Rounding can leave `target` just past the final weight.
+        last
+    }
+
+    /// Builds an alias table drawing each element with probability
+    /// proportional to its count raised to `power`.
+    ///
+    /// word2vec draws negative samples with a power of 0.75, which flattens
+    /// the distribution toward rarer elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// use bag::SplitMix64;
+    /// fn main() {
+    ///     let bag = bagof!("the", "the", "the", "the", "the", "the", "the", "the", "zebra");
+    ///     let table = bag.sampling_table(0.5);
+    ///     let mut rng = SplitMix64::new(7);
+    ///     let zebras = (0..4000)
+    ///         .filter(|_| table.sample(&mut rng) == Some(&"zebra"))
+    ///         .count();
+    ///     // sqrt(1) / (sqrt(8) + sqrt(1)) is about 26%.
+    ///     assert!(zebras > 900 && zebras < 1200);
+    /// }
+    /// ```
+    pub fn sampling_table(&self, power: f64) -> SamplingTable<'_, T> {
+        let elems: Vec<&T> = self.0.keys().collect();
+        let weights: Vec<f64> = self.0.values().map(|c| c.to_f64().powf(power)).collect();
+        let n = elems.len();
+        let total: f64 = weights.iter().sum();
+        let mut scaled: Vec<f64> = weights.iter().map(|w| w * n as f64 / total).collect();
+
+        // Vose's method: pair every under-full column with an over-full one.
+        let mut keep = vec![1.0; n];
+        let mut alias: Vec<usize> = (0..n).collect();
+        let (mut small, mut large): (Vec<usize>, Vec<usize>) =
+            (0..n).partition(|&i| scaled[i] < 1.0);
+        while let (Some(s), Some(&l)) = (small.pop(), large.last()) {
+            keep[s] = scaled[s];
+            alias[s] = l;
+            scaled[l] -= 1.0 - scaled[s];
+            if scaled[l] < 1.0 {
+                large.pop();
+                small.push(l);
+            }
+        }
+        SamplingTable { elems, keep, alias }
+    }
+
+    /// Yields the probability of keeping each occurrence of every element
+    /// under word2vec's frequent-word subsampling with the given
+    /// `threshold`, typically around `1e-5`.
+    ///
+    /// An element with relative frequency `f` is kept with probability
+    /// `(sqrt(f / threshold) + 1) * threshold / f`, capped at one, so only
+    /// elements much more frequent than `threshold` are thinned out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::Bag;
+    /// let mut bag = Bag::new();
+    /// for i in 0..10_000 {
+    ///     bag.put(if i % 2 == 0 { "the" } else { "rare" });
+    /// }
+    /// bag.put("unicorn");
+    /// let keep: std::collections::HashMap<_, _> = bag.subsample_probabilities(1e-3).collect();
+    /// assert!(keep[&"the"] < 0.1);
+    /// assert_eq!(1.0, keep[&"unicorn"]);
+    /// ```
+    pub fn subsample_probabilities<'a>(
+        &'a self,
+        threshold: f64,
+    ) -> impl Iterator<Item = (&'a T, f64)> + 'a {
+        let total = self.len().to_f64();
+        self.0.iter().map(move |(elem, &count)| {
+            let f = count.to_f64() / total;
+            let keep = ((f / threshold).sqrt() + 1.0) * threshold / f;
+            (elem, keep.min(1.0))
+        })
+    }
+
+    /// Builds cumulative weights once for repeated weighted draws.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// use bag::SplitMix64;
+    /// fn main() {
+    ///     let bag = bagof!('a', 'b', 'b');
+    ///     let sampler = bag.sampler();
+    ///     let mut rng = SplitMix64::new(3);
+    ///     assert!(bag.contains(sampler.sample(&mut rng).unwrap()));
+    /// }
+    /// ```
+    pub fn sampler(&self) -> Sampler<'_, T> {
+        let mut elems = Vec::with_capacity(self.0.len());
+        let mut cumulative = Vec::with_capacity(self.0.len());
+        let mut total = 0.0;
+        for (elem, &count) in &self.0 {
+            total += count.to_f64();
+            elems.push(elem);
+            cumulative.push(total);
+        }
+        Sampler { elems, cumulative }
+    }
+}
+
+impl<T: Eq + Hash + Clone, C: Count> Bag<T, C> {
+    /// Draws `k` elements with replacement, with probability proportional
+    /// to their counts, and returns the multinomial sample as a bag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// use bag::SplitMix64;
+    /// fn main() {
+    ///     let bag = bagof!('a', 'b');
+    ///     let drawn = bag.sample_n(10, &mut SplitMix64::new(5));
+    ///     assert_eq!(10, drawn.len());
+    ///     assert_eq!(2, bag.len());
+    /// }
+    /// ```
+    pub fn sample_n<R: RandomSource>(&self, k: usize, rng: &mut R) -> Self {
+        let sampler = self.sampler();
+        let mut drawn = Bag::default();
+        for _ in 0..k {
+            match sampler.sample(rng) {
+                Some(elem) => drawn.put(elem.clone()),
+                None => break,
+            }
+        }
+        drawn
+    }
+
+    /// Draws `k` elements without replacement, removing them from the bag
+    /// and returning them as a new bag. Draws everything if `k` exceeds the
+    /// number of elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// use bag::SplitMix64;
+    /// fn main() {
+    ///     let mut urn = bagof!("red", "red", "blue");
+    ///     let drawn = urn.draw_n(2, &mut SplitMix64::new(1));
+    ///     assert_eq!(2, drawn.len());
+    ///     assert_eq!(1, urn.len());
+    ///
+    ///     let rest = urn.draw_n(5, &mut SplitMix64::new(1));
+    ///     assert_eq!(1, rest.len());
+    ///     assert!(urn.is_empty());
+    /// }
+    /// ```
+    pub fn draw_n<R: RandomSource>(&mut self, k: usize, rng: &mut R) -> Self {
+        let mut drawn = Bag::default();
+        for _ in 0..k {
+            let elem = match self.sample(rng) {
+                Some(elem) => elem.clone(),
+                None => break,
+            };
+            let emptied = {
+                let count = self.0.get_mut(&elem).expect("sampled element");
+                *count -= C::ONE;
+                *count == C::ZERO
+            };
+            if emptied {
+                self.0.remove(&elem);
+            }
+            drawn.put(elem);
+        }
+        drawn
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use random::SplitMix64;
+    use Bag;
+
+    #[test]
+    fn proportional() {
+        let mut bag = Bag::new();
+        for (elem, n) in [('a', 1), ('b', 2), ('c', 7)].iter().cloned() {
+            for _ in 0..n {
+                bag.put(elem);
+            }
+        }
+        let sampler = bag.sampler();
+        let mut rng = SplitMix64::new(11);
+        let mut drawn = Bag::new();
+        for _ in 0..10000 {
+            drawn.put(*sampler.sample(&mut rng).unwrap());
+        }
+        assert!((drawn.occurrence(&'c') as f64 / 10000.0 - 0.7).abs() < 0.02);
+        assert!((drawn.occurrence(&'a') as f64 / 10000.0 - 0.1).abs() < 0.02);
+        assert_eq!(None, Bag::<char>::new().sample(&mut rng));
+        assert_eq!(None, Bag::<char>::new().sampler().sample(&mut rng));
+    }
+
+    #[test]
+    fn draw_preserves_totals() {
+        let mut urn = bagof!(1, 1, 1, 2, 2, 3);
+        let mut rng = SplitMix64::new(2);
+        let drawn = urn.draw_n(4, &mut rng);
+        assert_eq!(4, drawn.len());
+        for elem in 1..4 {
+            assert_eq!(
+                bagof!(1, 1, 1, 2, 2, 3).occurrence(&elem),
+                urn.occurrence(&elem) + drawn.occurrence(&elem)
+            );
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use std::cmp::Ordering;
+use std::collections::hash_map::Iter;
+use std::hash::Hash;
+
+use {Bag, Count};
+
+/// A lazy iterator over the multiset union of two bags, created by
+/// `Bag::union_iter`.
+#[derive(Debug, Clone)]
+pub struct Union<'a, T: Eq + Hash + 'a, C: Count + 'a> {
+    left: Iter<'a, T, C>,
+    right: Iter<'a, T, C>,
+    a: &'a Bag<T, C>,
+    b: &'a Bag<T, C>,
+}
+
+impl<'a, T: Eq + Hash, C: Count> Iterator for Union<'a, T, C> {
+    type Item = (&'a T, C);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some((elem, &count)) = self.left.next() {
+            return Some((elem, count.max(self.b.occurrence(elem))));
+        }
+        let a = self.a;
+        self.right
+            .by_ref()
+            .find(|&(elem, _)| !a.0.contains_key(elem))
+            .map(|(elem, &count)| (elem, count))
+    }
+}
+
+/// A lazy iterator over the multiset intersection of two bags, created by
+/// `Bag::intersection_iter`.
+#[derive(Debug, Clone)]
+pub struct Intersection<'a, T: Eq + Hash + 'a, C: Count + 'a> {
+    iter: Iter<'a, T, C>,
+    other: &'a Bag<T, C>,
+}
+
+impl<'a, T: Eq + Hash, C: Count> Iterator for Intersection<'a, T, C> {
+    type Item = (&'a T, C);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let other = self.other;
+        self.iter.by_ref().find_map(|(elem, &count)| {
+            other
+                .0
+                .get(elem)
+                .map(|&other_count| (elem, count.min(other_count)))
+        })
+    }
+}
+
+/// A lazy iterator over the multiset difference of two bags, created by
+/// `Bag::difference_iter`.
+#[derive(Debug, Clone)]
+pub struct Difference<'a, T: Eq + Hash + 'a, C: Count + 'a> {
+    iter: Iter<'a, T, C>,
+    other: &'a Bag<T, C>,
+}
+
+impl<'a, T: Eq + Hash, C: Count> Iterator for Difference<'a, T, C> {
+    type Item = (&'a T, C);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let other = self.other;
+        self.iter.by_ref().find_map(|(elem, &count)| {
+            let other_count = other.occurrence(elem);
+            if count > other_count {
+                Some((elem, count - other_count))
+            } else {
+                None
+            }
+        })
+    }
+}
+
+impl<T: Eq + Hash, C: Count> Bag<T, C> {
+    /// Iterates over every element of either bag with the larger of its two
+    /// counts, without building a new bag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let a = bagof!('x', 'x', 'y');
+    ///     let b = bagof!('x', 'z');
+    ///     let mut union: Vec<_> = a.union_iter(&b).collect();
+    ///     union.sort();
+    ///     assert_eq!(vec![(&'x', 2), (&'y', 1), (&'z', 1)], union);
+    /// }
+    /// ```
+    pub fn union_iter<'a>(&'a self, other: &'a Bag<T, C>) -> Union<'a, T, C> {
+        Union {
+            left: self.0.iter(),
+            right: other.0.iter(),
+            a: self,
+            b: other,
+        }
+    }
+
+    /// Iterates over the elements of both bags with the smaller of their two
+    /// counts, without building a new bag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let a = bagof!('x', 'x', 'y');
+    ///     let b = bagof!('x', 'z');
+    ///     assert_eq!(vec![(&'x', 1)], a.intersection_iter(&b).collect::<Vec<_>>());
+    /// }
+    /// ```
+    pub fn intersection_iter<'a>(&'a self, other: &'a Bag<T, C>) -> Intersection<'a, T, C> {
+        let (small, large) = if self.0.len() <= other.0.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        Intersection {
+            iter: small.0.iter(),
+            other: large,
+        }
+    }
+
+    /// Iterates over the elements of this bag with their counts reduced by
+    /// their counts in `other`, skipping those that drop to zero, without
+    /// building a new bag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let a = bagof!('x', 'x', 'y');
+    ///     let b = bagof!('x', 'y', 'z');
+    ///     assert_eq!(vec![(&'x', 1)], a.difference_iter(&b).collect::<Vec<_>>());
+    /// }
+    /// ```
+    pub fn difference_iter<'a>(&'a self, other: &'a Bag<T, C>) -> Difference<'a, T, C> {
+        Difference {
+            iter: self.0.iter(),
+            other,
+        }
+    }
+}
+
+/// Orders bags by multiset inclusion: `a <= b` when every element occurs in
+/// `a` at most as often as in `b`. Bags each holding something the other
+/// lacks are incomparable.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use(bagof)]
+/// extern crate bag;
+/// fn main() {
+///     let small = bagof!('a', 'b');
+///     let large = bagof!('a', 'a', 'b', 'c');
+///     assert!(small < large);
+///     assert_eq!(None, bagof!('z').partial_cmp(&large));
+/// }
+/// ```
+impl<T: Eq + Hash, C: Count> PartialOrd for Bag<T, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (other.includes(self), self.includes(other)) {
+            (true, true) => Some(Ordering::Equal),
+            (true, false) => Some(Ordering::Less),
+            (false, true) => Some(Ordering::Greater),
+            (false, false) => None,
+        }
+    }
+}
+
+impl<T: Eq + Hash, C: Count> Bag<T, C> {
+    /// Returns `true` if every element of `other` occurs in this bag at
+    /// least as often.
+    fn includes(&self, other: &Self) -> bool {
+        other.0.len() <= self.0.len()
+            && other
+                .0
+                .iter()
+                .all(|(elem, &count)| count <= self.occurrence(elem))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use Bag;
+
+    fn sorted<'a, I: Iterator<Item = (&'a char, usize)>>(iter: I) -> Vec<(char, usize)> {
+        let mut v: Vec<_> = iter.map(|(&e, c)| (e, c)).collect();
+        v.sort();
+        v
+    }
+
+    #[test]
+    fn set_operations() {
+        let a: Bag<char> = "aaabbc".chars().collect();
+        let b: Bag<char> = "abbbd".chars().collect();
+        assert_eq!(
+            vec![('a', 3), ('b', 3), ('c', 1), ('d', 1)],
+            sorted(a.union_iter(&b))
+        );
+        assert_eq!(vec![('a', 1), ('b', 2)], sorted(a.intersection_iter(&b)));
+        assert_eq!(vec![('a', 1), ('b', 2)], sorted(b.intersection_iter(&a)));
+        assert_eq!(vec![('a', 2), ('c', 1)], sorted(a.difference_iter(&b)));
+        assert_eq!(vec![('b', 1), ('d', 1)], sorted(b.difference_iter(&a)));
+    }
+
+    #[test]
+    fn inclusion_order() {
+        let a: Bag<char> = "aab".chars().collect();
+        assert!(a <= a.clone() && a >= a.clone());
+        assert!(Bag::new() < a);
+        assert_eq!(None, a.partial_cmp(&"abb".chars().collect()));
+    }
+}
@@ -0,0 +1,310 @@
+use std::collections::hash_map::Entry::{Occupied, Vacant};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use iter::Frequency;
+use {Bag, OverflowError};
+
+/// A multiset whose counts may go negative.
+///
+/// Mirrors the arithmetic of Python's `Counter`: subtracting more occurrences
+/// than are present leaves a negative count instead of saturating at zero.
+/// Elements whose count returns to zero are dropped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignedBag<T: Eq + Hash>(HashMap<T, isize>);
+
+impl<T: Eq + Hash> Default for SignedBag<T> {
+    fn default() -> Self {
+        SignedBag(HashMap::new())
+    }
+}
+
+/// Converts every count to `isize`.
+///
+/// # Panics
+///
+/// Panics if a count exceeds `isize::MAX`; see `SignedBag::try_from_bag`.
+impl<T: Eq + Hash> From<Bag<T>> for SignedBag<T> {
+    fn from(bag: Bag<T>) -> Self {
+        let mut signed = SignedBag(HashMap::with_capacity(bag.0.len()));
+        for (elem, count) in bag.0 {
+            signed.add(elem, to_signed(count));
+        }
+        signed
+    }
+}
+
+impl<T: Eq + Hash> SignedBag<T> {
+    /// Creates a new empty `SignedBag`.
+    pub fn new() -> Self {
+        SignedBag(HashMap::new())
+    }
+
+    /// Converts every count of `bag` to `isize` like `From`, or returns an
+    /// error if a count exceeds `isize::MAX`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::{Bag, OverflowError, SignedBag};
+    /// let bag: Bag<_> = Bag::from_frequencies(vec![("a", usize::max_value())]).unwrap();
+    /// assert_eq!(Err(OverflowError), SignedBag::try_from_bag(bag));
+    /// ```
+    pub fn try_from_bag(bag: Bag<T>) -> Result<Self, OverflowError> {
+        let mut signed = SignedBag(HashMap::with_capacity(bag.0.len()));
+        for (elem, count) in bag.0 {
+            signed.add(elem, try_to_signed(count)?);
+        }
+        Ok(signed)
+    }
+
+    /// Adds `delta` to the count of `elem`, which may be negative.
+    ///
+    /// # Panics
+    ///
+    /// Panics in debug builds if the count overflows `isize`; see `try_add`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::SignedBag;
+    /// let mut bag = SignedBag::new();
+    /// bag.add("a", 2);
+    /// bag.add("a", -5);
+    /// assert_eq!(-3, bag.occurrence(&"a"));
+    /// bag.add("a", 3);
+    /// assert!(bag.is_empty());
+    /// ```
+    pub fn add(&mut self, elem: T, delta: isize) {
+        match self.0.entry(elem) {
+            Vacant(view) => {
+                if delta != 0 {
+                    view.insert(delta);
+                }
+            }
+            Occupied(mut view) => {
+                *view.get_mut() += delta;
+                if *view.get() == 0 {
+                    view.remove();
+                }
+            }
+        }
+    }
+
+    /// Adds `delta` to the count of `elem` like `add`, returning the new
+    /// count, or an error leaving the bag unchanged on overflow.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::{OverflowError, SignedBag};
+    /// let mut bag = SignedBag::new();
+    /// bag.add("a", isize::min_value());
+    /// assert_eq!(Err(OverflowError), bag.try_add("a", -1));
+    /// assert_eq!(Ok(-1), bag.try_add("a", isize::max_value()));
+    /// ```
+    #[inline]
+    pub fn try_add(&mut self, elem: T, delta: isize) -> Result<isize, OverflowError> {
+        match self.0.entry(elem) {
+            Vacant(view) => {
+                if delta != 0 {
+                    view.insert(delta);
+                }
+                Ok(delta)
+            }
+            Occupied(mut view) => {
+                let count = view.get().checked_add(delta).ok_or(OverflowError)?;
+                if count == 0 {
+                    view.remove();
+                } else {
+                    *view.get_mut() = count;
+                }
+                Ok(count)
+            }
+        }
+    }
+
+    /// Insert an element.
+    pub fn put(&mut self, elem: T) {
+        self.add(elem, 1)
+    }
+
+    /// Returns the signed count of `elem`, zero when it is absent.
+    pub fn occurrence(&self, elem: &T) -> isize {
+        self.0.get(elem).map_or(0, |&x| x)
+    }
+
+    /// Returns `true` if every count is zero.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Sums all counts, positive and negative.
+    ///
+    /// # Panics
+    ///
+    /// Panics in debug builds if the sum overflows `isize`; see
+    /// `checked_total`.
+    pub fn total(&self) -> isize {
+        self.0.values().sum()
+    }
+
+    /// Sums all counts like `total`, returning `None` on overflow.
+    #[inline]
+    pub fn checked_total(&self) -> Option<isize> {
+        self.0
+            .values()
+            .try_fold(0isize, |total, &count| total.checked_add(count))
+    }
+
+    pub fn frequency(&self) -> Frequency<'_, T, isize> {
+        Frequency(self.0.iter())
+    }
+}
+
+impl<T: Eq + Hash + Clone> SignedBag<T> {
+    /// Adds every occurrence in `bag`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a count in `bag` exceeds `isize::MAX`, or in debug builds
+    /// if a count overflows `isize`; see `try_update`.
+    pub fn update(&mut self, bag: &Bag<T>) {
+        for (elem, &count) in &bag.0 {
+            self.add(elem.clone(), to_signed(count));
+        }
+    }
+
+    /// Subtracts every occurrence in `bag`, letting counts go negative.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a count in `bag` exceeds `isize::MAX`, or in debug builds
+    /// if a count overflows `isize`; see `try_subtract`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// use bag::SignedBag;
+    /// fn main() {
+    ///     let mut diff = SignedBag::from(bagof!("a", "a", "b"));
+    ///     diff.subtract(&bagof!("a", "b", "b", "c"));
+    ///     assert_eq!(1, diff.occurrence(&"a"));
+    ///     assert_eq!(-1, diff.occurrence(&"b"));
+    ///     assert_eq!(-1, diff.occurrence(&"c"));
+    /// }
+    /// ```
+    pub fn subtract(&mut self, bag: &Bag<T>) {
+        for (elem, &count) in &bag.0 {
+            self.add(elem.clone(), -to_signed(count));
+        }
+    }
+
+    /// Adds every occurrence in `bag` like `update`, or returns an error
+    /// leaving the bag unchanged if a count would overflow `isize`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// use bag::{OverflowError, SignedBag};
+    /// fn main() {
+    ///     let mut bag = SignedBag::new();
+    ///     bag.add("a", isize::max_value());
+    ///     assert_eq!(Err(OverflowError), bag.try_update(&bagof!("a", "b")));
+    ///     assert_eq!(0, bag.occurrence(&"b"));
+    /// }
+    /// ```
+    pub fn try_update(&mut self, bag: &Bag<T>) -> Result<(), OverflowError> {
+        self.try_apply(bag, false)
+    }
+
+    /// Subtracts every occurrence in `bag` like `subtract`, or returns an
+    /// error leaving the bag unchanged if a count would overflow `isize`.
+    pub fn try_subtract(&mut self, bag: &Bag<T>) -> Result<(), OverflowError> {
+        self.try_apply(bag, true)
+    }
+
+    /// Checks every new count before changing any of them.
+    fn try_apply(&mut self, bag: &Bag<T>, negate: bool) -> Result<(), OverflowError> {
+        let mut deltas = Vec::with_capacity(bag.0.len());
+        for (elem, &count) in &bag.0 {
+            let count = try_to_signed(count)?;
+            let delta = if negate { -count } else { count };
+            self.occurrence(elem)
+                .checked_add(delta)
+                .ok_or(OverflowError)?;
+            deltas.push((elem, delta));
+        }
+        for (elem, delta) in deltas {
+            self.add(elem.clone(), delta);
+        }
+        Ok(())
+    }
+
+    /// Collects the elements with a positive count into a `Bag`.
+    pub fn positive(&self) -> Bag<T> {
+        Bag(self
+            .0
+            .iter()
+            .filter(|&(_, &c)| c > 0)
+            .map(|(e, &c)| (e.clone(), c as usize))
+            .collect())
+    }
+
+    /// Collects the elements with a negative count into a `Bag`, keyed by
+    /// the magnitude of their count.
+    pub fn negative(&self) -> Bag<T> {
+        Bag(self
+            .0
+            .iter()
+            .filter(|&(_, &c)| c < 0)
+            .map(|(e, &c)| (e.clone(), c.unsigned_abs()))
+            .collect())
+    }
+}
+
+fn to_signed(count: usize) -> isize {
+    try_to_signed(count).expect("count overflows isize")
+}
+
+fn try_to_signed(count: usize) -> Result<isize, OverflowError> {
+    if count <= isize::MAX as usize {
+        Ok(count as isize)
+    } else {
+        Err(OverflowError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SignedBag;
+
+    #[test]
+    fn projections() {
+        let mut diff = SignedBag::from(bagof!('a', 'a', 'a', 'b'));
+        diff.subtract(&bagof!('a', 'b', 'b', 'b', 'c'));
+
+        assert_eq!(bagof!('a', 'a'), diff.positive());
+        assert_eq!(bagof!('b', 'b', 'c'), diff.negative());
+        assert_eq!(-1, diff.total());
+
+        diff.update(&bagof!('b', 'b', 'c'));
+        assert_eq!(bagof!('a', 'a'), diff.positive());
+        assert!(diff.negative().is_empty());
+    }
+
+    #[test]
+    fn try_subtract_is_atomic() {
+        let mut diff = SignedBag::new();
+        diff.add('a', isize::MIN + 1);
+        assert!(diff.try_subtract(&bagof!('a', 'a', 'b')).is_err());
+        assert_eq!(0, diff.occurrence(&'b'));
+        assert_eq!(Ok(()), diff.try_subtract(&bagof!('a', 'b')));
+        assert_eq!(isize::MIN, diff.occurrence(&'a'));
+        assert_eq!(-1, diff.occurrence(&'b'));
+    }
+}
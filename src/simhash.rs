@@ -0,0 +1,55 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use {Bag, Count};
+
+/// Counts the bits in which two fingerprints differ.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(2, bag::hamming_distance(0b1010, 0b0110));
+/// ```
+pub fn hamming_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+impl<T: Eq + Hash, C: Count> Bag<T, C> {
+    /// Computes a 64-bit SimHash fingerprint of the bag, weighting each
+    /// element by its count.
+    ///
+    /// Similar bags get fingerprints a small `hamming_distance` apart, so
+    /// near-duplicate documents can be found by comparing fingerprints
+    /// alone. The empty bag has fingerprint zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::{hamming_distance, Bag};
+    /// let a: Bag<_> = "the quick brown fox jumps over the lazy dog".split(' ').collect();
+    /// let b: Bag<_> = "the quick brown fox jumped over the lazy dog".split(' ').collect();
+    /// let c: Bag<_> = "lorem ipsum dolor sit amet consectetur".split(' ').collect();
+    /// assert!(hamming_distance(a.simhash(), b.simhash()) < hamming_distance(a.simhash(), c.simhash()));
+    /// ```
+    pub fn simhash(&self) -> u64 {
+        let mut weights = [0.0f64; 64];
+        for (elem, &count) in &self.0 {
+            let mut hasher = DefaultHasher::new();
+            elem.hash(&mut hasher);
+            let h = hasher.finish();
+            let w = count.to_f64();
+            for (bit, weight) in weights.iter_mut().enumerate() {
+                if h >> bit & 1 == 1 {
+                    *weight += w;
+                } else {
+                    *weight -= w;
+                }
+            }
+        }
+        weights
+            .iter()
+            .enumerate()
+            .filter(|&(_, &weight)| weight > 0.0)
+            .fold(0, |fingerprint, (bit, _)| fingerprint | 1 << bit)
+    }
+}
@@ -0,0 +1,315 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+use {Bag, Count};
+
+/// A count-min sketch: an approximate `Bag` in fixed memory.
+///
+/// `occurrence` never underestimates; it overestimates by at most
+/// `e / width` of the total count with probability `1 - exp(-depth)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CountMinSketch<T: Hash> {
+    width: usize,
+    depth: usize,
+    counters: Vec<usize>,
+    total: usize,
+    marker: PhantomData<fn(&T)>,
+}
+
+impl<T: Hash> CountMinSketch<T> {
+    /// Creates an empty sketch of `depth` rows of `width` counters each.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` or `depth` is zero.
+    pub fn new(width: usize, depth: usize) -> Self {
+        assert!(width > 0 && depth > 0, "sketch dimensions must be positive");
+        CountMinSketch {
+            width,
+            depth,
+            counters: vec![0; width * depth],
+            total: 0,
+            marker: PhantomData,
+        }
+    }
+
+    /// Creates an empty sketch whose estimates exceed the true count by at
+    /// most `epsilon` times the total count, with probability at least
+    /// `1 - delta`.
+    ///
+    /// # Panics
+    ///
+    /// Panics unless `epsilon` and `delta` are both in `(0, 1)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::CountMinSketch;
+    /// let sketch = CountMinSketch::<&str>::with_error(0.01, 0.01);
+    /// assert_eq!(272, sketch.width());
+    /// assert_eq!(5, sketch.depth());
+    /// ```
+    pub fn with_error(epsilon: f64, delta: f64) -> Self {
+        assert!(
+            epsilon > 0.0 && epsilon < 1.0,
+            "epsilon must be between 0 and 1"
+        );
+        assert!(delta > 0.0 && delta < 1.0, "delta must be between 0 and 1");
+        let width = (std::f64::consts::E / epsilon).ceil() as usize;
+        let depth = (1.0 / delta).ln().ceil().max(1.0) as usize;
+        CountMinSketch::new(width, depth)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Insert an element.
+    pub fn put(&mut self, elem: &T) {
+        self.add(elem, 1)
+    }
+
+    /// Adds `count` occurrences of `elem`.
+    pub fn add(&mut self, elem: &T, count: usize) {
+        let (h1, h2) = hashes(elem);
+        for row in 0..self.depth {
+            let i = self.index(row, h1, h2);
+            self.counters[i] += count;
+        }
+        self.total += count;
+    }
+
+    /// Estimates the occurrences of `elem`, never underestimating.
+    pub fn occurrence(&self, elem: &T) -> usize {
+        let (h1, h2) = hashes(elem);
+        (0..self.depth)
+            .map(|row| self.counters[self.index(row, h1, h2)])
+            .min()
+            .unwrap_or(0)
+    }
+
+    /// Counts all the occurrences added, exactly.
+    pub fn len(&self) -> usize {
+        self.total
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total == 0
+    }
+
+    fn index(&self, row: usize, h1: u64, h2: u64) -> usize {
+        let h = h1.wrapping_add((row as u64).wrapping_mul(h2));
+        row * self.width + (h % self.width as u64) as usize
+    }
+}
+
+impl<T: Eq + Hash> Bag<T> {
+    /// Summarizes the bag in a count-min sketch with the error bounds of
+    /// `CountMinSketch::with_error`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let bag = bagof!("a", "a", "b");
+    ///     let sketch = bag.to_sketch(0.01, 0.01);
+    ///     assert!(sketch.occurrence(&"a") >= 2);
+    ///     assert_eq!(3, sketch.len());
+    /// }
+    /// ```
+    pub fn to_sketch(&self, epsilon: f64, delta: f64) -> CountMinSketch<T> {
+        let mut sketch = CountMinSketch::with_error(epsilon, delta);
+        for (elem, &count) in &self.0 {
+            sketch.add(elem, count);
+        }
+        sketch
+    }
+}
+
+/// A HyperLogLog estimator of the number of distinct elements in a stream,
+/// in a fixed `2^precision` bytes.
+///
+/// The relative standard error is about `1.04 / sqrt(2^precision)`, so the
+/// default precision of 12 is accurate to roughly 1.6% in 4 KiB.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApproxDistinct<T: Hash> {
+    precision: u32,
+    registers: Vec<u8>,
+    marker: PhantomData<fn(&T)>,
+}
+
+impl<T: Hash> Default for ApproxDistinct<T> {
+    fn default() -> Self {
+        ApproxDistinct::new(12)
+    }
+}
+
+impl<T: Hash> ApproxDistinct<T> {
+    /// Creates an empty estimator with `2^precision` registers.
+    ///
+    /// # Panics
+    ///
+    /// Panics unless `precision` is between 4 and 18.
+    pub fn new(precision: u32) -> Self {
+        assert!(
+            (4..=18).contains(&precision),
+            "precision must be between 4 and 18"
+        );
+        ApproxDistinct {
+            precision,
+            registers: vec![0; 1 << precision],
+            marker: PhantomData,
+        }
+    }
+
+    pub fn precision(&self) -> u32 {
+        self.precision
+    }
+
+    /// Insert an element.
+    pub fn put(&mut self, elem: &T) {
+        let (h, _) = hashes(elem);
+        let i = (h >> (64 - self.precision)) as usize;
+        // The remaining bits, with a sentinel so the rank is bounded.
+        let rest = (h << self.precision) | (1 << (self.precision - 1));
+        let rank = rest.leading_zeros() as u8 + 1;
+        if rank > self.registers[i] {
+            self.registers[i] = rank;
+        }
+    }
+
+    /// Estimates the number of distinct elements inserted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::ApproxDistinct;
+    /// let mut distinct = ApproxDistinct::default();
+    /// for i in 0..100_000u32 {
+    ///     distinct.put(&(i % 5000));
+    /// }
+    /// let estimate = distinct.distinct_len() as f64;
+    /// assert!((estimate - 5000.0).abs() < 5000.0 * 0.05);
+    /// ```
+    pub fn distinct_len(&self) -> usize {
+        let m = self.registers.len() as f64;
+        let alpha = match self.registers.len() {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _ => 0.7213 / (1.0 + 1.079 / m),
+        };
+        let sum: f64 = self
+            .registers
+            .iter()
+            .map(|&r| 2f64.powi(-i32::from(r)))
+            .sum();
+        let estimate = alpha * m * m / sum;
+        let empty = self.registers.iter().filter(|&&r| r == 0).count();
+        if estimate <= 2.5 * m && empty > 0 {
+            // Linear counting is more accurate for small cardinalities.
+            (m * (m / empty as f64).ln()).round() as usize
+        } else {
+            estimate.round() as usize
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.registers.iter().all(|&r| r == 0)
+    }
+
+    /// Combines `other` into this estimator, as if every element inserted
+    /// into `other` had been inserted here.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the precisions differ.
+    pub fn merge(&mut self, other: &ApproxDistinct<T>) {
+        assert_eq!(
+            self.precision, other.precision,
+            "cannot merge estimators of different precision"
+        );
+        for (r, &o) in self.registers.iter_mut().zip(&other.registers) {
+            *r = (*r).max(o);
+        }
+    }
+}
+
+impl<T: Eq + Hash, C: Count> Bag<T, C> {
+    /// Builds a distinct-count estimator over the elements, to be merged
+    /// with estimators of streams the bag does not hold.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let bag = bagof!("a", "b", "a");
+    ///     assert_eq!(2, bag.approx_distinct(12).distinct_len());
+    /// }
+    /// ```
+    pub fn approx_distinct(&self, precision: u32) -> ApproxDistinct<T> {
+        let mut distinct = ApproxDistinct::new(precision);
+        for elem in self.0.keys() {
+            distinct.put(elem);
+        }
+        distinct
+    }
+}
+
+/// Derives the two base hashes for double hashing. The second is forced odd
+/// so that rows never collapse onto the same column sequence.
+fn hashes<T: Hash + ?Sized>(elem: &T) -> (u64, u64) {
+    let mut hasher = DefaultHasher::new();
+    elem.hash(&mut hasher);
+    let h = hasher.finish();
+    let mut hasher = DefaultHasher::new();
+    h.hash(&mut hasher);
+    (h, hasher.finish() | 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ApproxDistinct, CountMinSketch};
+
+    #[test]
+    fn never_underestimates() {
+        let mut sketch = CountMinSketch::new(64, 4);
+        for i in 0..1000u32 {
+            for _ in 0..(i % 5) {
+                sketch.put(&i);
+            }
+        }
+        for i in 0..1000u32 {
+            assert!(sketch.occurrence(&i) >= (i % 5) as usize);
+        }
+        assert_eq!(2000, sketch.len());
+    }
+
+    #[test]
+    fn merged_estimators_track_union() {
+        let mut a = ApproxDistinct::new(14);
+        let mut b = ApproxDistinct::new(14);
+        for i in 0..20_000u32 {
+            a.put(&i);
+            b.put(&(i + 10_000));
+        }
+        a.merge(&b);
+        let estimate = a.distinct_len() as f64;
+        assert!(
+            (estimate - 30_000.0).abs() < 30_000.0 * 0.03,
+            "{}",
+            estimate
+        );
+        assert!(ApproxDistinct::<u32>::default().is_empty());
+        assert_eq!(0, ApproxDistinct::<u32>::default().distinct_len());
+    }
+}
@@ -0,0 +1,223 @@
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+
+use {Bag, Count};
+
+/// Smoothed probabilities of the seen elements of a bag, together with the
+/// probability mass reserved for elements never seen, produced by
+/// `Bag::good_turing`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmoothedDistribution<T: Eq + Hash> {
+    probabilities: HashMap<T, f64>,
+    unseen: f64,
+}
+
+impl<T: Eq + Hash> SmoothedDistribution<T> {
+    /// Returns the smoothed probability of a seen `elem`, or zero for an
+    /// unseen one; see `unseen_mass` for the share of all unseen elements.
+    pub fn probability(&self, elem: &T) -> f64 {
+        self.probabilities.get(elem).map_or(0.0, |&p| p)
+    }
+
+    /// Returns the total probability of all the elements never seen.
+    pub fn unseen_mass(&self) -> f64 {
+        self.unseen
+    }
+
+    /// Returns the number of seen elements.
+    pub fn len(&self) -> usize {
+        self.probabilities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.probabilities.is_empty()
+    }
+
+    pub fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a T, f64)> + 'a {
+        self.probabilities.iter().map(|(elem, &p)| (elem, p))
+    }
+}
+
+impl<T: Eq + Hash, C: Count> Bag<T, C> {
+    /// Maps each count to the number of distinct elements occurring exactly
+    /// that often.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let bag = bagof!('a', 'b', 'r', 'a', 'c', 'a', 'd', 'a', 'b', 'r', 'a');
+    ///     let counts = bag.count_of_counts();
+    ///     assert_eq!(vec![(1, 2), (2, 2), (5, 1)], counts.into_iter().collect::<Vec<_>>());
+    /// }
+    /// ```
+    pub fn count_of_counts(&self) -> BTreeMap<C, usize> {
+        let mut counts = BTreeMap::new();
+        for &count in self.0.values() {
+            *counts.entry(count).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Returns the add-`k` smoothed probability of `elem` over a vocabulary
+    /// of `vocab_size` elements, `(count + k) / (len + k * vocab_size)`.
+    ///
+    /// With `k = 1` this is Laplace smoothing. The result is NaN when the
+    /// bag is empty and `k` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let bag = bagof!("spam", "spam", "ham");
+    ///     assert_eq!(0.6, bag.smoothed_probability(&"spam", 1.0, 2));
+    ///     assert_eq!(0.2, bag.smoothed_probability(&"eggs", 1.0, 2));
+    /// }
+    /// ```
+    pub fn smoothed_probability(&self, elem: &T, k: f64, vocab_size: usize) -> f64 {
+        (self.occurrence(elem).to_f64() + k) / (self.len().to_f64() + k * vocab_size as f64)
+    }
+
+    /// Estimates probabilities with Simple Good–Turing smoothing (Gale and
+    /// Sampson), shifting mass from seen elements to unseen ones.
+    ///
+    /// The unseen mass is the fraction of occurrences that are singletons.
+    /// Adjusted counts follow Turing's estimate while it is reliable and a
+    /// log-linear fit of the count-of-counts beyond that point. Elements
+    /// with equal counts get equal probabilities, and seen probabilities
+    /// plus the unseen mass sum to one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let bag = bagof!('a', 'a', 'a', 'b', 'b', 'c', 'd');
+    ///     let smoothed = bag.good_turing();
+    ///     assert!((smoothed.unseen_mass() - 2.0 / 7.0).abs() < 1e-12);
+    ///     assert!(smoothed.probability(&'a') > smoothed.probability(&'b'));
+    ///     assert_eq!(0.0, smoothed.probability(&'z'));
+    /// }
+    /// ```
+    pub fn good_turing(&self) -> SmoothedDistribution<T>
+    where
+        T: Clone,
+    {
+        let table = self.count_of_counts();
+        let counts: Vec<(f64, f64)> = table.iter().map(|(r, &n)| (r.to_f64(), n as f64)).collect();
+        let total = self.len().to_f64();
+        let singletons = counts
+            .iter()
+            .find(|&&(r, _)| r == 1.0)
+            .map_or(0.0, |&(_, n)| n);
+        let unseen = if total > 0.0 { singletons / total } else { 0.0 };
+
+        let adjusted = adjusted_counts(&counts);
+        let norm: f64 = counts
+            .iter()
+            .zip(&adjusted)
+            .map(|(&(_, n), &r_star)| n * r_star)
+            .sum();
+        let by_count: HashMap<C, f64> = table
+            .keys()
+            .zip(&adjusted)
+            .map(|(&r, &r_star)| (r, (1.0 - unseen) * r_star / norm))
+            .collect();
+        SmoothedDistribution {
+            probabilities: self
+                .0
+                .iter()
+                .map(|(elem, count)| (elem.clone(), by_count[count]))
+                .collect(),
+            unseen,
+        }
+    }
+}
+
+/// Computes the Simple Good–Turing adjusted count `r*` for every `(r, N_r)`
+/// pair, given in ascending order of `r`.
+fn adjusted_counts(counts: &[(f64, f64)]) -> Vec<f64> {
+    if counts.len() < 2 {
+        // Nothing to fit; every element has the same count.
+        return counts.iter().map(|&(r, _)| r).collect();
+    }
+
+    // Average N_r over the gaps between observed counts before fitting.
+    let points: Vec<(f64, f64)> = counts
+        .iter()
+        .enumerate()
+        .map(|(i, &(r, n))| {
+            let q = if i == 0 { 0.0 } else { counts[i - 1].0 };
+            let t = if i + 1 < counts.len() {
+                counts[i + 1].0
+            } else {
+                2.0 * r - q
+            };
+            (r.ln(), (2.0 * n / (t - q)).ln())
+        })
+        .collect();
+    let k = points.len() as f64;
+    let mean_x = points.iter().map(|p| p.0).sum::<f64>() / k;
+    let mean_y = points.iter().map(|p| p.1).sum::<f64>() / k;
+    let sxy: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+    let sxx: f64 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+    let slope = sxy / sxx;
+    // S(r + 1) / S(r) for the fitted S(r) = exp(a + b ln r).
+    let fitted = |r: f64| (r + 1.0) * ((r + 1.0) / r).powf(slope);
+
+    let mut use_fit = false;
+    counts
+        .iter()
+        .enumerate()
+        .map(|(i, &(r, n))| {
+            let y = fitted(r);
+            let next = counts.get(i + 1).filter(|&&(next_r, _)| next_r == r + 1.0);
+            match next {
+                Some(&(_, next_n)) if !use_fit => {
+                    let x = (r + 1.0) * next_n / n;
+                    let spread =
+                        1.96 * ((r + 1.0).powi(2) * next_n / (n * n) * (1.0 + next_n / n)).sqrt();
+                    if (x - y).abs() > spread {
+                        return x;
+                    }
+                    use_fit = true;
+                    y
+                }
+                _ => {
+                    use_fit = true;
+                    y
+                }
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use Bag;
+
+    #[test]
+    fn good_turing_sums_to_one() {
+        let mut bag = Bag::new();
+        for i in 1..200u32 {
+            // A Zipf-like tail: element e occurs about 200 / e times.
+            for _ in 0..(200 / i) {
+                bag.put(i);
+            }
+        }
+        let smoothed = bag.good_turing();
+        let seen: f64 = smoothed.iter().map(|(_, p)| p).sum();
+        assert!((seen + smoothed.unseen_mass() - 1.0).abs() < 1e-9);
+        assert!(smoothed.unseen_mass() > 0.0);
+        assert!(smoothed.probability(&1) > smoothed.probability(&2));
+
+        let flat = bagof!('a', 'b').good_turing();
+        assert_eq!(1.0, flat.unseen_mass());
+        assert!(Bag::<char>::new().good_turing().is_empty());
+    }
+}
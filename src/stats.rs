@@ -0,0 +1,496 @@
+use std::borrow::Borrow;
+use std::hash::Hash;
+
+use {Bag, Count};
+
+impl<T: Eq + Hash, C: Count> Bag<T, C> {
+    /// Computes the Shannon entropy of the relative frequencies, in bits.
+    ///
+    /// An empty bag has zero entropy.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     assert_eq!(0.0, bagof!('a', 'a').entropy());
+    ///     assert_eq!(1.0, bagof!('a', 'b').entropy());
+    ///     assert_eq!(2.0, bagof!('a', 'b', 'c', 'd').entropy());
+    /// }
+    /// ```
+    pub fn entropy(&self) -> f64 {
+        let total = self.len().to_f64();
+        -self
+            .0
+            .values()
+            .map(|&count| {
+                let p = count.to_f64() / total;
+                p * p.log2()
+            })
+            .sum::<f64>()
+    }
+
+    /// Computes the Kullback–Leibler divergence `D(self || other)` in bits,
+    /// treating both bags as empirical distributions.
+    ///
+    /// Returns infinity if `self` has an element `other` lacks; see
+    /// `kl_divergence_smoothed`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let p = bagof!('a', 'b');
+    ///     assert_eq!(0.0, p.kl_divergence(&p));
+    ///     let d = p.kl_divergence(&bagof!('a', 'b', 'b', 'b'));
+    ///     assert!((d - 0.2075).abs() < 1e-4);
+    ///     assert!(p.kl_divergence(&bagof!('a')).is_infinite());
+    /// }
+    /// ```
+    pub fn kl_divergence(&self, other: &Self) -> f64 {
+        self.kl_divergence_smoothed(other, 0.0)
+    }
+
+    /// Computes the Kullback–Leibler divergence like `kl_divergence`, with
+    /// add-`alpha` smoothing of `other` over the union of both bags'
+    /// elements so that unseen elements get non-zero probability.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let d = bagof!('a', 'b').kl_divergence_smoothed(&bagof!('a'), 1.0);
+    ///     assert!(d.is_finite() && d > 0.0);
+    /// }
+    /// ```
+    pub fn kl_divergence_smoothed(&self, other: &Self, alpha: f64) -> f64 {
+        let vocab = self.union_counts(other).count() as f64;
+        let p_total = self.len().to_f64();
+        let q_total = other.len().to_f64() + alpha * vocab;
+        if q_total == 0.0 {
+            return if self.is_empty() { 0.0 } else { f64::INFINITY };
+        }
+        self.0
+            .iter()
+            .map(|(elem, &count)| {
+                let p = count.to_f64() / p_total;
+                let q = (other.occurrence(elem).to_f64() + alpha) / q_total;
+                p * (p / q).log2()
+            })
+            .sum()
+    }
+
+    /// Scores the observed counts against `model` treated as a multinomial
+    /// distribution, returning the natural-log likelihood without the
+    /// multinomial coefficient.
+    ///
+    /// The model is add-`smoothing` smoothed over the union of both bags'
+    /// elements; with zero smoothing an element the model lacks makes the
+    /// likelihood negative infinity. Comparing scores of one bag against
+    /// several models is the core of naive Bayes classification.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let spam = bagof!("win", "win", "cash", "now");
+    ///     let ham = bagof!("meeting", "now", "lunch", "cash");
+    ///     let message = bagof!("win", "cash");
+    ///     assert!(message.log_likelihood(&spam, 1.0) > message.log_likelihood(&ham, 1.0));
+    ///     assert_eq!(f64::NEG_INFINITY, message.log_likelihood(&ham, 0.0));
+    /// }
+    /// ```
+    pub fn log_likelihood(&self, model: &Self, smoothing: f64) -> f64 {
+        let vocab = self.union_counts(model).count() as f64;
+        let total = model.len().to_f64() + smoothing * vocab;
+        if total == 0.0 {
+            return if self.is_empty() {
+                0.0
+            } else {
+                f64::NEG_INFINITY
+            };
+        }
+        self.0
+            .iter()
+            .map(|(elem, &count)| {
+                let p = (model.occurrence(elem).to_f64() + smoothing) / total;
+                count.to_f64() * p.ln()
+            })
+            .sum()
+    }
+
+    /// Returns the perplexity of `tokens` under the bag's relative
+    /// frequencies treated as a unigram model.
+    ///
+    /// Any token the bag lacks makes the perplexity infinite; an empty
+    /// sequence has perplexity one. See `lm::NGramModel::perplexity` for a
+    /// smoothed model with context.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let coin = bagof!("heads", "tails");
+    ///     assert_eq!(2.0, coin.unigram_perplexity(vec!["heads", "tails", "tails"]));
+    ///     assert_eq!(f64::INFINITY, coin.unigram_perplexity(vec!["edge"]));
+    /// }
+    /// ```
+    pub fn unigram_perplexity<I>(&self, tokens: I) -> f64
+    where
+        I: IntoIterator,
+        I::Item: Borrow<T>,
+    {
+        let total = self.len().to_f64();
+        let mut n = 0;
+        let mut log_prob = 0.0;
+        for token in tokens {
+            if total == 0.0 {
+                return f64::INFINITY;
+            }
+            n += 1;
+            log_prob += (self.occurrence(token.borrow()).to_f64() / total).ln();
+        }
+        match n {
+            0 => 1.0,
+            n => (-log_prob / n as f64).exp(),
+        }
+    }
+
+    /// Runs Pearson's chi-squared test of homogeneity on the two bags,
+    /// returning the statistic and its degrees of freedom.
+    ///
+    /// The bags form a 2×k contingency table over the k elements in either
+    /// bag, so there are k − 1 degrees of freedom. A large statistic for the
+    /// degrees of freedom means the bags are unlikely to be samples of the
+    /// same distribution.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let a = bagof!('x', 'x', 'y', 'y');
+    ///     assert_eq!((0.0, 1), a.chi_squared(&a));
+    ///     let (statistic, df) = a.chi_squared(&bagof!('x', 'x', 'x', 'x'));
+    ///     assert_eq!(1, df);
+    ///     assert!((statistic - 2.6667).abs() < 1e-4);
+    /// }
+    /// ```
+    pub fn chi_squared(&self, other: &Self) -> (f64, usize) {
+        let a_total = self.len().to_f64();
+        let b_total = other.len().to_f64();
+        let total = a_total + b_total;
+        let mut statistic = 0.0;
+        let mut k = 0;
+        for (_, a, b) in self.union_counts(other) {
+            k += 1;
+            let column = a.to_f64() + b.to_f64();
+            for &(observed, row) in &[(a.to_f64(), a_total), (b.to_f64(), b_total)] {
+                let expected = column * row / total;
+                if expected > 0.0 {
+                    statistic += (observed - expected).powi(2) / expected;
+                }
+            }
+        }
+        (statistic, k.max(1) - 1)
+    }
+
+    /// Computes the cosine similarity of the two bags as count vectors.
+    ///
+    /// Returns zero if either bag is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let a = bagof!("to", "be", "or", "not", "to", "be");
+    ///     assert!((a.cosine_similarity(&a) - 1.0).abs() < 1e-12);
+    ///     assert_eq!(0.0, a.cosine_similarity(&bagof!("question")));
+    /// }
+    /// ```
+    pub fn cosine_similarity(&self, other: &Self) -> f64 {
+        let (small, large) = if self.0.len() <= other.0.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        let dot: f64 = small
+            .0
+            .iter()
+            .map(|(elem, &count)| count.to_f64() * large.occurrence(elem).to_f64())
+            .sum();
+        if dot == 0.0 {
+            return 0.0;
+        }
+        dot / (self.norm() * other.norm())
+    }
+
+    /// Computes the Jaccard similarity of the sets of distinct elements.
+    ///
+    /// Two empty bags are identical and have similarity one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let a = bagof!('a', 'a', 'b', 'c');
+    ///     let b = bagof!('b', 'c', 'd');
+    ///     assert_eq!(0.5, a.jaccard(&b));
+    /// }
+    /// ```
+    pub fn jaccard(&self, other: &Self) -> f64 {
+        let (small, large) = if self.0.len() <= other.0.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        let shared = small
+            .0
+            .keys()
+            .filter(|elem| large.0.contains_key(elem))
+            .count();
+        let union = self.0.len() + other.0.len() - shared;
+        if union == 0 {
+            return 1.0;
+        }
+        shared as f64 / union as f64
+    }
+
+    /// Computes the weighted Jaccard (Ruzicka) similarity: the sum of the
+    /// smaller of each element's two counts over the sum of the larger.
+    ///
+    /// Two empty bags are identical and have similarity one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let a = bagof!('a', 'a', 'b');
+    ///     let b = bagof!('a', 'b', 'b', 'c');
+    ///     assert_eq!(2.0 / 5.0, a.weighted_jaccard(&b));
+    /// }
+    /// ```
+    pub fn weighted_jaccard(&self, other: &Self) -> f64 {
+        let (mut min, mut max) = (0.0, 0.0);
+        for (_, a, b) in self.union_counts(other) {
+            min += a.min(b).to_f64();
+            max += a.max(b).to_f64();
+        }
+        if max == 0.0 {
+            return 1.0;
+        }
+        min / max
+    }
+
+    /// Sums the absolute differences of the counts, the number of single
+    /// insertions and removals turning one bag into the other.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let a = bagof!('x', 'x', 'y');
+    ///     assert_eq!(0, a.l1_distance(&a));
+    ///     assert_eq!(4, a.l1_distance(&bagof!('x', 'z', 'z')));
+    /// }
+    /// ```
+    pub fn l1_distance(&self, other: &Self) -> C {
+        self.union_counts(other)
+            .map(|(_, a, b)| abs_diff(a, b))
+            .sum()
+    }
+
+    /// Returns `true` if `l1_distance` is at most `max`, stopping as soon
+    /// as the running distance exceeds it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let a = bagof!('x', 'x', 'y');
+    ///     assert!(a.within_distance(&bagof!('x', 'y'), 1));
+    ///     assert!(!a.within_distance(&bagof!('z'), 3));
+    /// }
+    /// ```
+    pub fn within_distance(&self, other: &Self, max: C) -> bool {
+        let mut distance = C::ZERO;
+        for (_, a, b) in self.union_counts(other) {
+            distance = match distance.checked_add(abs_diff(a, b)) {
+                Some(d) if d <= max => d,
+                _ => return false,
+            };
+        }
+        true
+    }
+
+    /// Computes the Euclidean distance between the two bags as count
+    /// vectors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let a = bagof!('x', 'x', 'x', 'y');
+    ///     assert_eq!(5.0, a.l2_distance(&bagof!('y', 'y', 'y', 'y', 'y')));
+    /// }
+    /// ```
+    pub fn l2_distance(&self, other: &Self) -> f64 {
+        self.union_counts(other)
+            .map(|(_, a, b)| (a.to_f64() - b.to_f64()).powi(2))
+            .sum::<f64>()
+            .sqrt()
+    }
+
+    /// Computes the Hellinger distance between the relative frequencies of
+    /// the two bags, which is symmetric and lies in `[0, 1]`.
+    ///
+    /// An empty bag is at distance one from any non-empty bag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let a = bagof!('x', 'y');
+    ///     assert_eq!(0.0, a.hellinger(&bagof!('x', 'x', 'y', 'y')));
+    ///     assert_eq!(1.0, a.hellinger(&bagof!('z')));
+    /// }
+    /// ```
+    pub fn hellinger(&self, other: &Self) -> f64 {
+        self.distribution_distance(other, |pairs| {
+            let sum: f64 = pairs.map(|(p, q)| (p.sqrt() - q.sqrt()).powi(2)).sum();
+            (sum / 2.0).sqrt().min(1.0)
+        })
+    }
+
+    /// Computes the total variation distance between the relative
+    /// frequencies of the two bags: half their L1 distance, or the largest
+    /// difference in probability the two can assign to any set of elements.
+    ///
+    /// An empty bag is at distance one from any non-empty bag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let a = bagof!('x', 'y');
+    ///     assert_eq!(0.5, a.total_variation(&bagof!('x', 'x')));
+    ///     assert_eq!(1.0, a.total_variation(&bagof!('z')));
+    /// }
+    /// ```
+    pub fn total_variation(&self, other: &Self) -> f64 {
+        self.distribution_distance(other, |pairs| {
+            let sum: f64 = pairs.map(|(p, q)| (p - q).abs()).sum();
+            (sum / 2.0).min(1.0)
+        })
+    }
+
+    /// Applies `distance` to the `(p, q)` probability pairs of the two
+    /// bags' relative frequencies over the union of their elements.
+    ///
+    /// Two empty bags are at distance zero and an empty bag is at distance
+    /// one from a non-empty one, without calling `distance`.
+    fn distribution_distance<F>(&self, other: &Self, distance: F) -> f64
+    where
+        F: FnOnce(&mut dyn Iterator<Item = (f64, f64)>) -> f64,
+    {
+        match (self.is_empty(), other.is_empty()) {
+            (true, true) => 0.0,
+            (true, false) | (false, true) => 1.0,
+            (false, false) => {
+                let p_total = self.len().to_f64();
+                let q_total = other.len().to_f64();
+                let mut pairs = self
+                    .union_counts(other)
+                    .map(|(_, a, b)| (a.to_f64() / p_total, b.to_f64() / q_total));
+                distance(&mut pairs)
+            }
+        }
+    }
+
+    /// Returns the Euclidean norm of the count vector.
+    fn norm(&self) -> f64 {
+        self.0
+            .values()
+            .map(|&count| count.to_f64() * count.to_f64())
+            .sum::<f64>()
+            .sqrt()
+    }
+
+    /// Iterates over the union of the elements of both bags with their
+    /// counts in each, visiting every element once.
+    fn union_counts<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = (&'a T, C, C)> + 'a {
+        let left = self
+            .0
+            .iter()
+            .map(move |(elem, &count)| (elem, count, other.occurrence(elem)));
+        let right = other
+            .0
+            .iter()
+            .filter(move |&(elem, _)| !self.0.contains_key(elem))
+            .map(|(elem, &count)| (elem, C::ZERO, count));
+        left.chain(right)
+    }
+}
+
+fn abs_diff<C: Count>(a: C, b: C) -> C {
+    if a > b {
+        a - b
+    } else {
+        b - a
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use Bag;
+
+    #[test]
+    fn kl_divergence_against_empty() {
+        let empty = Bag::<&str>::new();
+        assert_eq!(
+            f64::INFINITY,
+            bagof!("a", "b").kl_divergence_smoothed(&empty, 0.0)
+        );
+        assert_eq!(0.0, empty.kl_divergence_smoothed(&empty, 0.0));
+    }
+
+    #[test]
+    fn log_likelihood_against_empty() {
+        let empty = Bag::<&str>::new();
+        assert_eq!(f64::NEG_INFINITY, bagof!("a").log_likelihood(&empty, 0.0));
+        assert_eq!(0.0, empty.log_likelihood(&empty, 0.0));
+    }
+
+    #[test]
+    fn unigram_perplexity_of_empty_bag() {
+        let empty = Bag::<&str>::new();
+        assert_eq!(f64::INFINITY, empty.unigram_perplexity(vec!["a"]));
+        assert_eq!(1.0, empty.unigram_perplexity(Vec::<&str>::new()));
+    }
+}
@@ -0,0 +1,107 @@
+use std::collections::hash_map::Entry::{Occupied, Vacant};
+use std::fmt::Display;
+use std::hash::Hash;
+use std::io::{BufRead, Write};
+use std::str::FromStr;
+
+use error::TableError;
+use {Bag, Count};
+
+impl<T: Eq + Hash + Display, C: Count + Display> Bag<T, C> {
+    /// Writes one `element,count` line per distinct element, in arbitrary
+    /// order.
+    pub fn write_csv<W: Write>(&self, w: W) -> std::io::Result<()> {
+        self.write_delimited(w, ',')
+    }
+
+    /// Writes one line per distinct element holding the element and its
+    /// count separated by `delimiter`, in arbitrary order.
+    ///
+    /// Elements are written as is, without quoting.
+    pub fn write_delimited<W: Write>(&self, mut w: W, delimiter: char) -> std::io::Result<()> {
+        for (elem, count) in &self.0 {
+            writeln!(w, "{}{}{}", elem, delimiter, count)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Eq + Hash + FromStr, C: Count + FromStr> Bag<T, C> {
+    /// Reads `element,count` lines, as written by `write_csv`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::Bag;
+    /// let table = "the,3\ncat,1\n";
+    /// let bag: Bag<String> = Bag::read_csv(table.as_bytes()).unwrap();
+    /// assert_eq!(3, bag.occurrence(&"the".to_string()));
+    ///
+    /// let mut out = Vec::new();
+    /// bag.write_csv(&mut out).unwrap();
+    /// assert_eq!(bag, Bag::read_csv(&out[..]).unwrap());
+    /// ```
+    pub fn read_csv<R: BufRead>(r: R) -> Result<Self, TableError> {
+        Bag::read_delimited(r, ',')
+    }
+
+    /// Reads lines holding an element and its count separated by
+    /// `delimiter`.
+    ///
+    /// The count follows the last delimiter on the line, so elements may
+    /// contain the delimiter themselves. Blank lines are skipped, and an
+    /// element listed more than once gets the sum of its counts.
+    pub fn read_delimited<R: BufRead>(r: R, delimiter: char) -> Result<Self, TableError> {
+        let mut bag = Bag::default();
+        for (i, line) in r.lines().enumerate() {
+            let line = line?;
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() {
+                continue;
+            }
+            let invalid = TableError::InvalidLine(i + 1);
+            let (elem, count) = match line.rfind(delimiter) {
+                Some(at) => (&line[..at], &line[at + delimiter.len_utf8()..]),
+                None => return Err(invalid),
+            };
+            let elem: T = elem.parse().map_err(|_| TableError::InvalidLine(i + 1))?;
+            let count: C = match count.trim().parse() {
+                Ok(count) if count != C::ZERO => count,
+                _ => return Err(invalid),
+            };
+            match bag.0.entry(elem) {
+                Vacant(view) => {
+                    view.insert(count);
+                }
+                Occupied(mut view) => {
+                    *view.get_mut() = view.get().checked_add(count).ok_or(invalid)?;
+                }
+            }
+        }
+        Ok(bag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use error::TableError;
+    use Bag;
+
+    #[test]
+    fn tab_separated() {
+        let table = "a\tb\t2\n\nc\t1\r\na\tb\t1\n";
+        let bag: Bag<String> = Bag::read_delimited(table.as_bytes(), '\t').unwrap();
+        assert_eq!(3, bag.occurrence(&"a\tb".to_string()));
+        assert_eq!(1, bag.occurrence(&"c".to_string()));
+    }
+
+    #[test]
+    fn rejects_bad_lines() {
+        for &(table, line) in &[("a,1\nb\n", 2), ("a,0\n", 1), ("a,x\n", 1)] {
+            match Bag::<String>::read_csv(table.as_bytes()) {
+                Err(TableError::InvalidLine(n)) => assert_eq!(line, n),
+                other => panic!("{:?}", other),
+            }
+        }
+    }
+}
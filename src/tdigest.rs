@@ -0,0 +1,232 @@
+use std::cmp::Ordering;
+use std::hash::Hash;
+
+use {Bag, Count};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Centroid {
+    mean: f64,
+    weight: f64,
+}
+
+/// A merging t-digest answering approximate quantile queries over weighted
+/// values in bounded memory.
+///
+/// `compression` trades accuracy for size: the digest keeps on the order of
+/// `compression` centroids, with the finest resolution near the tails.
+#[derive(Debug, Clone)]
+pub struct TDigest {
+    compression: f64,
+    centroids: Vec<Centroid>,
+    buffer: Vec<Centroid>,
+    total: f64,
+    min: f64,
+    max: f64,
+}
+
+impl TDigest {
+    /// Creates an empty digest.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `compression` is not positive.
+    pub fn new(compression: f64) -> Self {
+        assert!(compression > 0.0, "compression must be positive");
+        TDigest {
+            compression,
+            centroids: Vec::new(),
+            buffer: Vec::new(),
+            total: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    /// Adds `value` with the given `weight`. Non-positive weights and NaN
+    /// values are ignored.
+    pub fn add(&mut self, value: f64, weight: f64) {
+        if weight <= 0.0 || value.is_nan() {
+            return;
+        }
+        self.buffer.push(Centroid {
+            mean: value,
+            weight,
+        });
+        self.total += weight;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        if self.buffer.len() as f64 >= self.compression * 5.0 {
+            self.compress();
+        }
+    }
+
+    /// Returns the total weight added so far.
+    pub fn total_weight(&self) -> f64 {
+        self.total
+    }
+
+    /// Estimates the value below which a fraction `q` of the weight lies,
+    /// or `None` if the digest is empty. `q` is clamped to `[0, 1]`.
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        if self.buffer.is_empty() {
+            self.quantile_of(q, &self.centroids)
+        } else {
+            let mut merged = self.clone();
+            merged.compress();
+            merged.quantile_of(q, &merged.centroids)
+        }
+    }
+
+    /// Merges all buffered values into the centroid list.
+    pub fn compress(&mut self) {
+        if self.buffer.is_empty() {
+            return;
+        }
+        let mut all = Vec::with_capacity(self.centroids.len() + self.buffer.len());
+        all.append(&mut self.centroids);
+        all.append(&mut self.buffer);
+        all.sort_by(|a, b| a.mean.partial_cmp(&b.mean).unwrap_or(Ordering::Equal));
+
+        let total = self.total;
+        let mut merged = Vec::new();
+        let mut current = all[0];
+        let mut before = 0.0;
+        for next in all.into_iter().skip(1) {
+            let proposed = current.weight + next.weight;
+            let q0 = before / total;
+            let q2 = (before + proposed) / total;
+            let limit = 4.0 * total * (q0 * (1.0 - q0)).min(q2 * (1.0 - q2)) / self.compression;
+            if proposed <= limit {
+                current.mean += (next.mean - current.mean) * next.weight / proposed;
+                current.weight = proposed;
+            } else {
+                before += current.weight;
+                merged.push(current);
+                current = next;
+            }
+        }
+        merged.push(current);
+        self.centroids = merged;
+    }
+
+    fn quantile_of(&self, q: f64, centroids: &[Centroid]) -> Option<f64> {
+        let first = centroids.first()?;
+        let last = centroids[centroids.len() - 1];
+        if centroids.len() == 1 {
+            return Some(first.mean);
+        }
+        let target = q.clamp(0.0, 1.0) * self.total;
+
+        // Each centroid's mean sits at the middle of its share of the weight.
+        let mut center = first.weight / 2.0;
+        if target < center {
+            return Some(lerp(self.min, first.mean, target / center));
+        }
+        for pair in centroids.windows(2) {
+            let next = center + (pair[0].weight + pair[1].weight) / 2.0;
+            if target <= next {
+                return Some(lerp(
+                    pair[0].mean,
+                    pair[1].mean,
+                    (target - center) / (next - center),
+                ));
+            }
+            center = next;
+        }
+        let rest = self.total - center;
+        Some(lerp(last.mean, self.max, (target - center) / rest))
+    }
+}
+
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+    a + (b - a) * t
+}
+
+/// Integer keys that `Bag::key_tdigest` can place on the real line,
+/// possibly rounding very large ones. Floats are not `Eq + Hash`, so they
+/// cannot be bag keys.
+pub trait ToF64: Copy {
+    /// Converts the key, rounding to the nearest representable value.
+    fn to_f64(self) -> f64;
+}
+
+macro_rules! impl_to_f64 {
+    ( $( $t:ty ),* ) => {
+        $(
+            impl ToF64 for $t {
+                #[inline]
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+            }
+        )*
+    };
+}
+
+impl_to_f64!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl<T: Eq + Hash + ToF64, C: Count> Bag<T, C> {
+    /// Builds a t-digest over the elements, weighting each distinct key by
+    /// its count, without expanding duplicates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::Bag;
+    /// let mut bag = Bag::new();
+    /// for i in 0..1000u32 {
+    ///     bag.put(i % 100);
+    /// }
+    /// let digest = bag.key_tdigest(100.0);
+    /// let median = digest.quantile(0.5).unwrap();
+    /// assert!((median - 49.5).abs() < 2.0);
+    /// ```
+    pub fn key_tdigest(&self, compression: f64) -> TDigest {
+        let mut digest = TDigest::new(compression);
+        for (&elem, &count) in &self.0 {
+            digest.add(ToF64::to_f64(elem), count.to_f64());
+        }
+        digest.compress();
+        digest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TDigest;
+
+    #[test]
+    fn uniform_quantiles() {
+        let mut digest = TDigest::new(100.0);
+        for i in 0..10000 {
+            digest.add(i as f64, 1.0);
+        }
+        for &q in &[0.01, 0.1, 0.5, 0.9, 0.99] {
+            let estimate = digest.quantile(q).unwrap();
+            assert!(
+                (estimate - q * 10000.0).abs() < 50.0,
+                "q={} got {}",
+                q,
+                estimate
+            );
+        }
+        assert_eq!(Some(0.0), digest.quantile(0.0));
+        assert_eq!(Some(9999.0), digest.quantile(1.0));
+    }
+
+    #[test]
+    fn wide_integer_keys() {
+        let mut bag = ::Bag::new();
+        for i in 0..1000u64 {
+            bag.put(u64::MAX - i % 10);
+        }
+        let digest = bag.key_tdigest(100.0);
+        assert_eq!(Some(u64::MAX as f64), digest.quantile(1.0));
+        assert_eq!(1000.0, digest.total_weight());
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(None, TDigest::new(50.0).quantile(0.5));
+    }
+}
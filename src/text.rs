@@ -0,0 +1,238 @@
+//! Building bags from text.
+
+use std::borrow::Cow;
+use std::collections::HashSet;
+
+use Bag;
+
+/// Splits text into the tokens counted by `Bag::bag_of_words`.
+pub trait Tokenizer {
+    /// Calls `emit` with every token of `text`, in order. Tokens are
+    /// borrowed from `text` where possible.
+    fn for_each_token<'a>(&self, text: &'a str, emit: &mut dyn FnMut(Cow<'a, str>));
+}
+
+/// Splits on whitespace, keeping punctuation attached to words.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Whitespace {
+    /// Lowercase every token.
+    pub lowercase: bool,
+}
+
+impl Tokenizer for Whitespace {
+    fn for_each_token<'a>(&self, text: &'a str, emit: &mut dyn FnMut(Cow<'a, str>)) {
+        for token in text.split_whitespace() {
+            emit(fold(token, self.lowercase));
+        }
+    }
+}
+
+/// Splits on every character that is not alphanumeric, so punctuation is
+/// stripped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Alphanumeric {
+    /// Lowercase every token.
+    pub lowercase: bool,
+}
+
+impl Tokenizer for Alphanumeric {
+    fn for_each_token<'a>(&self, text: &'a str, emit: &mut dyn FnMut(Cow<'a, str>)) {
+        for token in text.split(|c: char| !c.is_alphanumeric()) {
+            if !token.is_empty() {
+                emit(fold(token, self.lowercase));
+            }
+        }
+    }
+}
+
+/// Lowercases `token` if asked to, borrowing when it is already lowercase.
+fn fold(token: &str, lowercase: bool) -> Cow<'_, str> {
+    if lowercase && token.chars().any(char::is_uppercase) {
+        Cow::Owned(token.to_lowercase())
+    } else {
+        Cow::Borrowed(token)
+    }
+}
+
+/// Builds word bags through a `Tokenizer`, skipping stop words and short
+/// tokens before they are counted.
+///
+/// # Examples
+///
+/// ```
+/// use bag::text::{Alphanumeric, BagBuilder};
+/// let builder = BagBuilder::new(Alphanumeric { lowercase: true })
+///     .stop_words(vec!["the", "and"])
+///     .min_len(2);
+/// let words = builder.build("The cat and the hat, a story");
+/// assert_eq!(3, words.len());
+/// assert!(!words.contains("the"));
+/// assert!(!words.contains("a"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct BagBuilder<K: Tokenizer> {
+    tokenizer: K,
+    stop_words: HashSet<String>,
+    min_len: usize,
+}
+
+impl<K: Tokenizer> BagBuilder<K> {
+    /// Creates a builder counting every token of `tokenizer`.
+    pub fn new(tokenizer: K) -> Self {
+        BagBuilder {
+            tokenizer,
+            stop_words: HashSet::new(),
+            min_len: 0,
+        }
+    }
+
+    /// Adds words to skip. They are compared against tokens after the
+    /// tokenizer's own normalization, such as lowercasing.
+    pub fn stop_words<I, S>(mut self, words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.stop_words.extend(words.into_iter().map(Into::into));
+        self
+    }
+
+    /// Skips tokens shorter than `min_len` characters.
+    pub fn min_len(mut self, min_len: usize) -> Self {
+        self.min_len = min_len;
+        self
+    }
+
+    /// Counts the kept tokens of `text` into a new bag.
+    pub fn build(&self, text: &str) -> Bag<String> {
+        let mut bag = Bag::new();
+        self.count_into(&mut bag, text);
+        bag
+    }
+
+    /// Counts the kept tokens of `text` into `bag`.
+    pub fn count_into(&self, bag: &mut Bag<String>, text: &str) {
+        self.tokenizer.for_each_token(text, &mut |token| {
+            if token.chars().count() < self.min_len || self.stop_words.contains(&*token) {
+                return;
+            }
+            match token {
+                Cow::Borrowed(token) => bag.put_with(token, || token.to_string()),
+                Cow::Owned(token) => bag.put(token),
+            }
+        });
+    }
+}
+
+impl Bag<char> {
+    /// Counts the characters of `text`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::Bag;
+    /// let letters = Bag::chars_of("abracadabra");
+    /// assert_eq!(5, letters.occurrence(&'a'));
+    /// ```
+    pub fn chars_of(text: &str) -> Self {
+        let mut bag = Bag::new();
+        for c in text.chars() {
+            bag.put(c);
+        }
+        bag
+    }
+}
+
+impl<'a> Bag<&'a str> {
+    /// Counts the whitespace-separated words of `text`, borrowing them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::Bag;
+    /// let words = Bag::words_of("the cat and the hat");
+    /// assert_eq!(2, words.occurrence(&"the"));
+    /// ```
+    pub fn words_of(text: &'a str) -> Self {
+        let mut bag = Bag::new();
+        for word in text.split_whitespace() {
+            bag.put(word);
+        }
+        bag
+    }
+}
+
+impl Bag<String> {
+    /// Counts the overlapping `n`-character substrings of `text`. Counts
+    /// nothing when `n` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::Bag;
+    /// let grams = Bag::char_ngrams("banana", 2);
+    /// assert_eq!(2, grams.occurrence(&"an".to_string()));
+    /// assert_eq!(5, grams.len());
+    /// ```
+    pub fn char_ngrams(text: &str, n: usize) -> Self {
+        let mut bag = Bag::new();
+        if n == 0 {
+            return bag;
+        }
+        let mut bounds: Vec<usize> = text.char_indices().map(|(i, _)| i).collect();
+        bounds.push(text.len());
+        for pair in bounds.windows(n + 1) {
+            bag.put_with(&text[pair[0]..pair[n]], || {
+                text[pair[0]..pair[n]].to_string()
+            });
+        }
+        bag
+    }
+
+    /// Counts the tokens `tokenizer` finds in `text`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::text::Alphanumeric;
+    /// use bag::Bag;
+    /// let tokenizer = Alphanumeric { lowercase: true };
+    /// let words = Bag::bag_of_words("The cat. THE hat!", &tokenizer);
+    /// assert_eq!(2, words.occurrence(&"the".to_string()));
+    /// assert_eq!(1, words.occurrence(&"hat".to_string()));
+    /// ```
+    pub fn bag_of_words<K: Tokenizer + ?Sized>(text: &str, tokenizer: &K) -> Self {
+        let mut bag = Bag::new();
+        tokenizer.for_each_token(text, &mut |token| match token {
+            Cow::Borrowed(token) => bag.put_with(token, || token.to_string()),
+            Cow::Owned(token) => bag.put(token),
+        });
+        bag
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Alphanumeric, Whitespace};
+    use Bag;
+
+    #[test]
+    fn tokenizers() {
+        let text = "Hello, hello world";
+        let words = Bag::bag_of_words(text, &Whitespace::default());
+        assert_eq!(1, words.occurrence(&"Hello,".to_string()));
+
+        let words = Bag::bag_of_words(text, &Alphanumeric { lowercase: true });
+        assert_eq!(2, words.occurrence(&"hello".to_string()));
+        assert_eq!(3, words.len());
+    }
+
+    #[test]
+    fn multibyte_ngrams() {
+        let grams = Bag::char_ngrams("héhé", 2);
+        assert_eq!(2, grams.occurrence(&"hé".to_string()));
+        assert_eq!(1, grams.occurrence(&"éh".to_string()));
+        assert!(Bag::char_ngrams("abc", 4).is_empty());
+        assert!(Bag::char_ngrams("abc", 0).is_empty());
+    }
+}
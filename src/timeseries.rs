@@ -0,0 +1,257 @@
+use std::collections::BTreeMap;
+use std::hash::Hash;
+
+use Bag;
+
+const MINUTE: u64 = 60;
+const HOUR: u64 = 60 * MINUTE;
+const DAY: u64 = 24 * HOUR;
+
+/// One resolution level of a `RollupPolicy`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tier {
+    /// Width of each bucket, in seconds.
+    pub width: u64,
+    /// How long buckets of this width are kept before being merged into the
+    /// next tier, or `None` to keep them forever.
+    pub retention: Option<u64>,
+}
+
+/// The resolutions a `TimeSeriesBag` rolls its buckets through as they age.
+///
+/// Tiers are ordered from finest to coarsest; every width must be a multiple
+/// of the previous one so buckets merge without straddling boundaries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RollupPolicy {
+    tiers: Vec<Tier>,
+}
+
+impl RollupPolicy {
+    /// Creates a policy from `tiers`, finest first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tiers` is empty, a width is zero or not a multiple of the
+    /// previous width, or a tier other than the last has no retention.
+    pub fn new(tiers: Vec<Tier>) -> Self {
+        assert!(!tiers.is_empty(), "a rollup policy needs at least one tier");
+        for (i, tier) in tiers.iter().enumerate() {
+            assert!(tier.width > 0, "tier width must be positive");
+            if i > 0 {
+                assert!(
+                    tier.width % tiers[i - 1].width == 0,
+                    "tier widths must be multiples of the previous tier"
+                );
+            }
+            if i + 1 < tiers.len() {
+                assert!(
+                    tier.retention.is_some(),
+                    "only the last tier may keep buckets forever"
+                );
+            }
+        }
+        RollupPolicy { tiers }
+    }
+
+    pub fn tiers(&self) -> &[Tier] {
+        &self.tiers
+    }
+}
+
+impl Default for RollupPolicy {
+    /// Per-minute buckets for two hours, per-hour buckets for seven days and
+    /// per-day buckets forever.
+    fn default() -> Self {
+        RollupPolicy::new(vec![
+            Tier {
+                width: MINUTE,
+                retention: Some(2 * HOUR),
+            },
+            Tier {
+                width: HOUR,
+                retention: Some(7 * DAY),
+            },
+            Tier {
+                width: DAY,
+                retention: None,
+            },
+        ])
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Bucket<T: Eq + Hash> {
+    tier: usize,
+    bag: Bag<T>,
+}
+
+/// A bag of timestamped occurrences, bucketed by time and compacted into
+/// coarser buckets as it ages according to a `RollupPolicy`.
+///
+/// Timestamps are seconds since an arbitrary epoch. Compaction does not run
+/// on its own: call `compact` periodically, e.g. from a maintenance task.
+#[derive(Debug, Clone)]
+pub struct TimeSeriesBag<T: Eq + Hash> {
+    policy: RollupPolicy,
+    buckets: BTreeMap<u64, Bucket<T>>,
+}
+
+impl<T: Eq + Hash> Default for TimeSeriesBag<T> {
+    fn default() -> Self {
+        TimeSeriesBag::new(RollupPolicy::default())
+    }
+}
+
+impl<T: Eq + Hash> TimeSeriesBag<T> {
+    /// Creates an empty series rolled up according to `policy`.
+    pub fn new(policy: RollupPolicy) -> Self {
+        TimeSeriesBag {
+            policy,
+            buckets: BTreeMap::new(),
+        }
+    }
+
+    pub fn policy(&self) -> &RollupPolicy {
+        &self.policy
+    }
+
+    /// Records an occurrence of `elem` at `timestamp` in the finest tier.
+    pub fn put(&mut self, timestamp: u64, elem: T) {
+        let width = self.policy.tiers[0].width;
+        let start = timestamp - timestamp % width;
+        self.buckets
+            .entry(start)
+            .or_insert_with(|| Bucket {
+                tier: 0,
+                bag: Bag::new(),
+            })
+            .bag
+            .put(elem);
+    }
+
+    /// Returns the number of buckets currently held.
+    pub fn bucket_len(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// Iterates over the buckets in time order as `(start, width, bag)`.
+    pub fn buckets<'a>(&'a self) -> impl Iterator<Item = (u64, u64, &'a Bag<T>)> + 'a {
+        let tiers = &self.policy.tiers;
+        self.buckets
+            .iter()
+            .map(move |(&start, bucket)| (start, tiers[bucket.tier].width, &bucket.bag))
+    }
+
+    /// Merges every bucket that has outlived its tier's retention, measured
+    /// from the end of the bucket to `now`, into the next coarser tier.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::TimeSeriesBag;
+    /// let mut series = TimeSeriesBag::default();
+    /// for minute in 0..180 {
+    ///     series.put(minute * 60, "hit");
+    /// }
+    /// assert_eq!(180, series.bucket_len());
+    ///
+    /// series.compact(180 * 60);
+    /// // The first hour has aged out of per-minute retention.
+    /// assert_eq!(1 + 120, series.bucket_len());
+    /// assert_eq!(180, series.range(0, 180 * 60).occurrence(&"hit"));
+    /// ```
+    pub fn compact(&mut self, now: u64) {
+        loop {
+            let expired = self.expired(now);
+            if expired.is_empty() {
+                return;
+            }
+            for start in expired {
+                let bucket = self.buckets.remove(&start).expect("expired bucket");
+                let tier = bucket.tier + 1;
+                let width = self.policy.tiers[tier].width;
+                let target = self
+                    .buckets
+                    .entry(start - start % width)
+                    .or_insert_with(|| Bucket {
+                        tier,
+                        bag: Bag::new(),
+                    });
+                // Late arrivals may leave a finer bucket inside a coarser one.
+                target.tier = target.tier.max(tier);
+                target.bag.merge(bucket.bag);
+            }
+        }
+    }
+
+    /// Returns the starts of the buckets due to move to a coarser tier, in
+    /// ascending order.
+    fn expired(&self, now: u64) -> Vec<u64> {
+        let tiers = &self.policy.tiers;
+        self.buckets
+            .iter()
+            .filter(|&(&start, bucket)| {
+                let tier = tiers[bucket.tier];
+                bucket.tier + 1 < tiers.len()
+                    && tier
+                        .retention
+                        .is_some_and(|retention| start + tier.width + retention <= now)
+            })
+            .map(|(&start, _)| start)
+            .collect()
+    }
+}
+
+impl<T: Eq + Hash + Clone> TimeSeriesBag<T> {
+    /// Sums every bucket starting in `[from, to)` into a single bag.
+    pub fn range(&self, from: u64, to: u64) -> Bag<T> {
+        let mut total = Bag::new();
+        for bucket in self.buckets.range(from..to).map(|(_, bucket)| bucket) {
+            for (elem, &count) in &bucket.bag.0 {
+                *total.0.entry(elem.clone()).or_insert(0) += count;
+            }
+        }
+        total
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RollupPolicy, Tier, TimeSeriesBag, DAY, HOUR};
+
+    #[test]
+    fn cascades_through_tiers() {
+        let mut series = TimeSeriesBag::default();
+        series.put(0, 'a');
+        series.put(90, 'a');
+        series.put(HOUR + 5, 'b');
+
+        series.compact(9 * DAY);
+        let buckets: Vec<_> = series.buckets().collect();
+        assert_eq!(1, buckets.len());
+        assert_eq!((0, DAY), (buckets[0].0, buckets[0].1));
+        assert_eq!(bagof!('a', 'a', 'b'), *buckets[0].2);
+    }
+
+    #[test]
+    fn keeps_recent_buckets() {
+        let policy = RollupPolicy::new(vec![
+            Tier {
+                width: 10,
+                retention: Some(20),
+            },
+            Tier {
+                width: 100,
+                retention: None,
+            },
+        ]);
+        let mut series = TimeSeriesBag::new(policy);
+        for t in 0..100 {
+            series.put(t, t % 2);
+        }
+        series.compact(100);
+        // Buckets ending at or before 80 are rolled into [0, 100).
+        assert_eq!(3, series.bucket_len());
+        assert_eq!(50, series.range(0, 100).occurrence(&0));
+    }
+}
@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use {Bag, Count};
+
+/// Normalizes inputs into the keys a `TransformBag` counts, such as word
+/// stems or case-folded tokens.
+///
+/// Any `Fn(I) -> K` closure is a `KeyTransform`; plug a stemmer in by
+/// wrapping it in one.
+pub trait KeyTransform<I> {
+    /// The key type produced.
+    type Key: Eq + Hash;
+
+    fn transform(&self, input: I) -> Self::Key;
+}
+
+impl<I, K: Eq + Hash, F: Fn(I) -> K> KeyTransform<I> for F {
+    type Key = K;
+
+    fn transform(&self, input: I) -> K {
+        self(input)
+    }
+}
+
+/// Folds string input to lowercase.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Lowercase;
+
+impl<'a> KeyTransform<&'a str> for Lowercase {
+    type Key = String;
+
+    fn transform(&self, input: &'a str) -> String {
+        input.to_lowercase()
+    }
+}
+
+/// A `Bag` that passes every input through a `KeyTransform` on insert and
+/// on lookup.
+///
+/// # Examples
+///
+/// ```
+/// use bag::{Lowercase, TransformBag};
+/// let mut bag = TransformBag::new(Lowercase);
+/// for word in "The cat saw the dog".split(' ') {
+///     bag.put(word);
+/// }
+/// assert_eq!(2, bag.occurrence("THE"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct TransformBag<I, K: KeyTransform<I>> {
+    transform: K,
+    bag: Bag<K::Key>,
+}
+
+impl<I, K: KeyTransform<I>> TransformBag<I, K> {
+    /// Creates an empty bag keyed through `transform`.
+    pub fn new(transform: K) -> Self {
+        TransformBag {
+            transform,
+            bag: Bag::new(),
+        }
+    }
+
+    /// Insert an input under its transformed key.
+    pub fn put(&mut self, input: I) {
+        let key = self.transform.transform(input);
+        self.bag.put(key);
+    }
+
+    /// Counts the occurrences of the key `input` transforms to.
+    pub fn occurrence(&self, input: I) -> usize {
+        self.bag.occurrence(&self.transform.transform(input))
+    }
+
+    /// Returns the underlying bag of transformed keys.
+    pub fn bag(&self) -> &Bag<K::Key> {
+        &self.bag
+    }
+
+    /// Consumes the wrapper, returning the underlying bag.
+    pub fn into_bag(self) -> Bag<K::Key> {
+        self.bag
+    }
+}
+
+impl<T: Eq + Hash, C: Count> Bag<T, C> {
+    /// Aggregates the counts under the key `f` derives from each element.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let words = bagof!("a", "an", "to", "the", "the");
+    ///     let lengths = words.counts_by(|word| word.len());
+    ///     assert_eq!(2, lengths.occurrence(&2));
+    ///     assert_eq!(2, lengths.occurrence(&3));
+    /// }
+    /// ```
+    pub fn counts_by<K, F>(&self, f: F) -> Bag<K, C>
+    where
+        K: Eq + Hash,
+        F: Fn(&T) -> K,
+    {
+        let mut grouped = Bag::default();
+        for (elem, &count) in &self.0 {
+            *grouped.0.entry(f(elem)).or_insert(C::ZERO) += count;
+        }
+        grouped
+    }
+
+    /// Applies `f` to every element, summing the counts of elements that
+    /// map to the same key.
+    ///
+    /// Unlike `counts_by`, this consumes the bag and hands `f` each element
+    /// by value, so no clones are needed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let words = bagof!("The".to_string(), "the".to_string(), "cat".to_string());
+    ///     let folded = words.map_keys(|word| word.to_lowercase());
+    ///     assert_eq!(2, folded.occurrence(&"the".to_string()));
+    ///     assert_eq!(2, folded.distinct_len());
+    /// }
+    /// ```
+    pub fn map_keys<U, F>(self, mut f: F) -> Bag<U, C>
+    where
+        U: Eq + Hash,
+        F: FnMut(T) -> U,
+    {
+        let mut mapped = Bag(HashMap::with_capacity(self.0.len()));
+        for (elem, count) in self.0 {
+            *mapped.0.entry(f(elem)).or_insert(C::ZERO) += count;
+        }
+        mapped
+    }
+
+    /// Keeps the `n` most frequent elements other than `unk` and folds the
+    /// occurrences of all the others into `unk`, returning how many
+    /// occurrences were folded.
+    ///
+    /// Elements tied at the cut-off are kept or folded arbitrarily. Any
+    /// occurrences `unk` already had are kept and not counted as folded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let mut bag = bagof!("the", "the", "the", "cat", "cat", "sat", "mat");
+    ///     assert_eq!(2, bag.prune_to_top(2, "<unk>"));
+    ///     assert_eq!(bagof!("the", "the", "the", "cat", "cat", "<unk>", "<unk>"), bag);
+    /// }
+    /// ```
+    pub fn prune_to_top(&mut self, n: usize, unk: T) -> C {
+        let unk_count = self.0.remove(&unk).unwrap_or(C::ZERO);
+        let mut folded = C::ZERO;
+        if self.0.len() > n {
+            let mut entries: Vec<(T, C)> = self.0.drain().collect();
+            if n > 0 {
+                entries.select_nth_unstable_by(n - 1, |a, b| b.1.cmp(&a.1));
+            }
+            for (_, count) in entries.drain(n..) {
+                folded += count;
+            }
+            self.0.extend(entries);
+        }
+        let total = unk_count + folded;
+        if total != C::ZERO {
+            self.0.insert(unk, total);
+        }
+        folded
+    }
+
+    /// Splits the bag in two, returning the elements for which `f` returns
+    /// `true` first and the rest second.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let bag = bagof!("the", "the", "the", "cat", "sat");
+    ///     let (frequent, rare) = bag.partition(|_, count| count > 1);
+    ///     assert_eq!(bagof!("the", "the", "the"), frequent);
+    ///     assert_eq!(bagof!("cat", "sat"), rare);
+    /// }
+    /// ```
+    pub fn partition<F>(mut self, f: F) -> (Self, Self)
+    where
+        F: Fn(&T, C) -> bool,
+    {
+        let matching = self.split_off(f);
+        (matching, self)
+    }
+
+    /// Moves the elements for which `f` returns `true` out of the bag and
+    /// returns them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let mut words = bagof!("the", "cat", "a", "the");
+    ///     let stop_words = words.split_off(|word, _| ["a", "the"].contains(word));
+    ///     assert_eq!(bagof!("the", "a", "the"), stop_words);
+    ///     assert_eq!(bagof!("cat"), words);
+    /// }
+    /// ```
+    pub fn split_off<F>(&mut self, f: F) -> Self
+    where
+        F: Fn(&T, C) -> bool,
+    {
+        let (matching, rest) = self.0.drain().partition(|(elem, count)| f(elem, *count));
+        self.0 = rest;
+        Bag(matching)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TransformBag;
+
+    #[test]
+    fn closure_transform() {
+        let mut bag = TransformBag::new(|word: &str| word.trim_end_matches('s').to_string());
+        for word in "cats cat dogs".split(' ') {
+            bag.put(word);
+        }
+        assert_eq!(2, bag.occurrence("cat"));
+        assert_eq!(1, bag.occurrence("dogs"));
+        assert_eq!(2, bag.bag().distinct_len());
+    }
+}
@@ -0,0 +1,290 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+
+use corpus::Corpus;
+use error::TableError;
+use {Bag, Count};
+
+/// A mapping of elements to dense `u32` ids, for turning bags into feature
+/// vectors.
+///
+/// Ids are assigned in insertion order starting from zero and never change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Vocabulary<E: Eq + Hash> {
+    ids: HashMap<E, u32>,
+    elems: Vec<E>,
+}
+
+impl<E: Eq + Hash> Default for Vocabulary<E> {
+    fn default() -> Self {
+        Vocabulary {
+            ids: HashMap::new(),
+            elems: Vec::new(),
+        }
+    }
+}
+
+impl<E: Eq + Hash + Clone> Vocabulary<E> {
+    /// Creates an empty vocabulary.
+    pub fn new() -> Self {
+        Vocabulary::default()
+    }
+
+    /// Returns the id of `elem`, assigning the next free one if it is new.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vocabulary already holds `u32::MAX` elements.
+    pub fn insert(&mut self, elem: E) -> u32 {
+        if let Some(&id) = self.ids.get(&elem) {
+            return id;
+        }
+        assert!(self.elems.len() < u32::MAX as usize, "vocabulary is full");
+        let id = self.elems.len() as u32;
+        self.ids.insert(elem.clone(), id);
+        self.elems.push(elem);
+        id
+    }
+}
+
+impl<E: Eq + Hash + Ord + Clone> Vocabulary<E> {
+    /// Builds a vocabulary of the elements occurring at least `min_count`
+    /// times in `bag`.
+    ///
+    /// More frequent elements get smaller ids, with ties broken by element
+    /// order, so the same bag always yields the same ids.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// use bag::Vocabulary;
+    /// fn main() {
+    ///     let vocab = Vocabulary::from_bag(&bagof!("b", "a", "b", "c", "a", "b"), 2);
+    ///     assert_eq!(Some(0), vocab.id_of(&"b"));
+    ///     assert_eq!(Some(&"a"), vocab.element_of(1));
+    ///     assert_eq!(None, vocab.id_of(&"c"));
+    /// }
+    /// ```
+    pub fn from_bag(bag: &Bag<E>, min_count: usize) -> Self {
+        Vocabulary::from_bags(Some(bag), min_count)
+    }
+
+    /// Builds a vocabulary like `from_bag`, over the summed counts of all
+    /// of `bags`.
+    pub fn from_bags<'a, I>(bags: I, min_count: usize) -> Self
+    where
+        I: IntoIterator<Item = &'a Bag<E>>,
+        E: 'a,
+    {
+        let mut totals: HashMap<&E, usize> = HashMap::new();
+        for bag in bags {
+            for (elem, &count) in &bag.0 {
+                *totals.entry(elem).or_insert(0) += count;
+            }
+        }
+        let mut elems: Vec<(&E, usize)> = totals
+            .into_iter()
+            .filter(|&(_, count)| count >= min_count)
+            .collect();
+        elems.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+
+        let mut vocab = Vocabulary::new();
+        for (elem, _) in elems {
+            vocab.insert(elem.clone());
+        }
+        vocab
+    }
+}
+
+impl<E: Eq + Hash> Vocabulary<E> {
+    /// Returns the element with id `id`.
+    pub fn element_of(&self, id: u32) -> Option<&E> {
+        self.elems.get(id as usize)
+    }
+
+    /// Iterates over the elements in id order.
+    pub fn elements(&self) -> std::slice::Iter<'_, E> {
+        self.elems.iter()
+    }
+
+    /// Writes one element per line in id order, so that `read` restores the
+    /// same ids.
+    pub fn write<W: Write>(&self, mut w: W) -> io::Result<()>
+    where
+        E: Display,
+    {
+        for elem in &self.elems {
+            writeln!(w, "{}", elem)?;
+        }
+        Ok(())
+    }
+
+    /// Reads a vocabulary written by `write`, assigning ids by line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bag::Vocabulary;
+    /// let mut vocab = Vocabulary::new();
+    /// vocab.insert("cat".to_string());
+    /// vocab.insert("dog".to_string());
+    /// let mut out = Vec::new();
+    /// vocab.write(&mut out).unwrap();
+    /// assert_eq!(vocab, Vocabulary::read(&out[..]).unwrap());
+    /// ```
+    pub fn read<R: BufRead>(r: R) -> Result<Self, TableError>
+    where
+        E: FromStr + Clone,
+    {
+        let mut vocab = Vocabulary::default();
+        for (i, line) in r.lines().enumerate() {
+            let line = line?;
+            let elem: E = line
+                .trim_end_matches('\r')
+                .parse()
+                .map_err(|_| TableError::InvalidLine(i + 1))?;
+            if vocab.ids.contains_key(&elem) {
+                return Err(TableError::InvalidLine(i + 1));
+            }
+            vocab.insert(elem);
+        }
+        Ok(vocab)
+    }
+
+    /// Returns the id of `elem`, if it is in the vocabulary.
+    pub fn id_of(&self, elem: &E) -> Option<u32> {
+        self.ids.get(elem).cloned()
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.elems.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elems.is_empty()
+    }
+}
+
+/// The feature value `Bag::to_sparse_vec` assigns to each element.
+#[derive(Debug)]
+pub enum Weighting<'a, E: Eq + Hash + 'a> {
+    /// The raw count.
+    Count,
+    /// One for every element present.
+    Binary,
+    /// The TF-IDF weight against a corpus, as in `Corpus::tf_idf`.
+    TfIdf(&'a Corpus<E>),
+}
+
+// Derived impls would needlessly require `E: Copy`.
+impl<'a, E: Eq + Hash> Clone for Weighting<'a, E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, E: Eq + Hash> Copy for Weighting<'a, E> {}
+
+impl<E: Eq + Hash> Bag<E> {
+    /// Converts the bag into `(id, value)` pairs sorted by id, dropping
+    /// elements missing from `vocab`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// use bag::{Vocabulary, Weighting};
+    /// fn main() {
+    ///     let mut vocab = Vocabulary::new();
+    ///     vocab.insert("cat");
+    ///     vocab.insert("sat");
+    ///     let doc = bagof!("sat", "cat", "cat", "mat");
+    ///     assert_eq!(vec![(0, 2.0), (1, 1.0)], doc.to_sparse_vec(&vocab, Weighting::Count));
+    ///     assert_eq!(vec![(0, 1.0), (1, 1.0)], doc.to_sparse_vec(&vocab, Weighting::Binary));
+    /// }
+    /// ```
+    pub fn to_sparse_vec(&self, vocab: &Vocabulary<E>, weighting: Weighting<E>) -> Vec<(u32, f64)> {
+        let len = self.len() as f64;
+        let mut features: Vec<(u32, f64)> = self
+            .0
+            .iter()
+            .filter_map(|(elem, &count)| {
+                let id = vocab.id_of(elem)?;
+                let value = match weighting {
+                    Weighting::Count => count as f64,
+                    Weighting::Binary => 1.0,
+                    Weighting::TfIdf(corpus) => count as f64 / len * corpus.idf(elem),
+                };
+                Some((id, value))
+            })
+            .collect();
+        features.sort_by_key(|&(id, _)| id);
+        features
+    }
+}
+
+impl<E: Eq + Hash, C: Count> Bag<E, C> {
+    /// Projects the counts into a dense vector of `dims` values with the
+    /// hashing trick, without a vocabulary.
+    ///
+    /// Each element adds its count to the slot its hash selects, with a
+    /// sign taken from another bit of the hash so that collisions cancel
+    /// out on average instead of piling up.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dims` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use(bagof)]
+    /// extern crate bag;
+    /// fn main() {
+    ///     let features = bagof!("a", "b", "b").hashed_features(1 << 10);
+    ///     assert_eq!(1024, features.len());
+    ///     let mass: f64 = features.iter().map(|x| x.abs()).sum();
+    ///     assert_eq!(3.0, mass);
+    /// }
+    /// ```
+    pub fn hashed_features(&self, dims: usize) -> Vec<f64> {
+        assert!(dims > 0, "feature dimension must be positive");
+        let mut features = vec![0.0; dims];
+        for (elem, &count) in &self.0 {
+            let mut hasher = DefaultHasher::new();
+            elem.hash(&mut hasher);
+            let h = hasher.finish();
+            let sign = if h >> 63 == 1 { -1.0 } else { 1.0 };
+            features[(h % dims as u64) as usize] += sign * count.to_f64();
+        }
+        features
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Vocabulary, Weighting};
+    use corpus::Corpus;
+
+    #[test]
+    fn tf_idf_features() {
+        let mut corpus = Corpus::new();
+        corpus.insert("a", bagof!('x', 'y'));
+        corpus.insert("b", bagof!('x'));
+        let mut vocab = Vocabulary::new();
+        assert_eq!(0, vocab.insert('x'));
+        assert_eq!(1, vocab.insert('y'));
+        assert_eq!(0, vocab.insert('x'));
+
+        let features = bagof!('x', 'y').to_sparse_vec(&vocab, Weighting::TfIdf(&corpus));
+        assert_eq!(vec![(0, 0.0), (1, 0.5 * 2f64.ln())], features);
+    }
+}